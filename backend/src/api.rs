@@ -0,0 +1,1103 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+
+use crate::carsharing::{
+    BlockReason, Car, CarClass, CarSharing, ClassReservation, CarSharingService, Charge, car_status_name, Damage, MembershipPlan, Notification, Person, Reservation, ReservationEvent, ReservationRecord, ReservationState, ServiceRecord,
+    Station,
+};
+use crate::allocation::{compare_strategies, AllocationOutcome};
+use crate::clock::{ClockMode, SharedClock};
+use crate::geo::NearbyCar;
+use crate::rebalancing::{plan_rebalancing, RebalancingPlan};
+use crate::scenario::{run_scenario, ScenarioConfig, ScenarioReport};
+use crate::forecast::{car_forecast, due_soon, fleet_forecast, MaintenanceForecast};
+use crate::metrics::load_snapshots;
+use crate::notifications::{self, OutboxEntry};
+use crate::webhooks::{self, Webhook, WebhookDelivery, EVENT_TYPES};
+use crate::statistics::{expiring_licenses, fleet_statistics, DailySnapshot, FleetStatistics, LicenseExpiry};
+use crate::{load_state, SharedCarIndex};
+
+// Standard- und Maximalgröße einer Seite
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 200;
+// Größter erlaubter Suchradius für die Umkreissuche
+const MAX_RADIUS_KM: f64 = 500.0;
+const DEFAULT_RADIUS_KM: f64 = 5.0;
+// Standardzeitraum für die Liste ablaufender Führerscheine (Tage)
+const DEFAULT_LICENSE_WARN_DAYS: u32 = 30;
+
+// -------------------- Envelopes --------------------
+
+/// Antwort für Listen: Daten plus Metadaten zum Blättern
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub meta: PageMeta,
+}
+
+#[derive(Serialize)]
+pub struct PageMeta {
+    pub total: usize,
+    pub limit: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Antwort für einzelne Objekte
+#[derive(Serialize)]
+pub struct Item<T> {
+    pub data: T,
+}
+
+/// Fehlerantwort, z.B. {"error": {"code": "not_found", "message": "..."}}
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Serialize)]
+struct ErrorDetail {
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, code: "bad_request", message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::NOT_FOUND, code: "not_found", message: message.into() }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::UNAUTHORIZED, code: "unauthorized", message: message.into() }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::FORBIDDEN, code: "forbidden", message: message.into() }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::CONFLICT, code: "conflict", message: message.into() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail { code: self.code, message: self.message },
+        };
+        let mut response = (self.status, Json(body)).into_response();
+        // 401 sagt dem Aufrufer, welche Anmeldung erwartet wird
+        if self.status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+pub type ApiResult<T> = Result<Json<T>, ApiError>;
+
+// -------------------- Query-Parameter --------------------
+
+#[derive(Deserialize)]
+pub struct PersonQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub status: Option<String>,
+    pub block_reason: Option<String>,
+    // Name des gebuchten Tarifs
+    pub plan: Option<String>,
+    // Nur Personen, deren Führerschein in höchstens so vielen Tagen abläuft
+    pub license_expires_within: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct CarQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub status: Option<String>,
+    pub min_mileage: Option<u32>,
+    pub max_mileage: Option<u32>,
+    pub station_id: Option<String>,
+    pub class: Option<String>,
+    pub min_seats: Option<u8>,
+}
+
+#[derive(Deserialize)]
+pub struct ReservationQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    pub car_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClassReservationQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    pub class: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReservationHistoryQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    pub state: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReservationEventQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    // Nur Ereignisse mit größerer ID (zum fortlaufenden Abholen)
+    pub since_id: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct RentalQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    pub car_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DayRangeQuery {
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct LicenseQuery {
+    pub within_days: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+    pub person_id: Option<String>,
+    // Nur Nachrichten mit größerer ID (zum fortlaufenden Abholen)
+    pub since_id: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+    pub status: Option<String>,
+    pub channel: Option<String>,
+    pub person_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub order: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ForecastQuery {
+    // Nur Autos, deren Wartung/TÜV in höchstens so vielen Tagen fällig ist
+    pub due_within_days: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct NearbyQuery {
+    pub lat: f64,
+    pub lon: f64,
+    pub radius_km: Option<f64>,
+    pub class: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Station mit aktueller Belegung
+#[derive(Serialize)]
+pub struct StationInfo {
+    #[serde(flatten)]
+    pub station: Station,
+    pub occupancy: u32,
+    pub available_cars: Vec<String>,
+}
+
+/// Betriebsart der Uhr und aktueller Geschäftstag
+#[derive(Serialize)]
+pub struct ClockInfo {
+    pub mode: ClockMode,
+    pub timezone: String,
+    pub current_day: u32,
+    pub minute_of_day: u32,
+    // Minuten seit Beginn von Tag 0 (Grundlage aller Zeitfenster)
+    pub now: u64,
+    // Heutiges Datum in der Zeitzone
+    pub local_date: String,
+    // Nur im Echtzeitbetrieb: wann der nächste Tag beginnt (RFC 3339, UTC)
+    pub next_day_at: Option<String>,
+}
+
+/// Tarif mit der Anzahl der Personen, die ihn gebucht haben
+#[derive(Serialize)]
+pub struct PlanInfo {
+    #[serde(flatten)]
+    pub plan: MembershipPlan,
+    pub subscribers: u32,
+}
+
+/// Nachricht mit ihren Zustellungen je Kanal
+#[derive(Serialize)]
+pub struct NotificationDelivery {
+    pub notification: Notification,
+    pub deliveries: Vec<OutboxEntry>,
+}
+
+/// Anfrage zum Anlegen eines Webhooks
+#[derive(Deserialize)]
+pub struct NewWebhook {
+    pub url: String,
+    // Ereignistypen aus EVENT_TYPES oder "*"
+    pub events: Vec<String>,
+    // Ohne Angabe wird ein zufälliges Geheimnis erzeugt
+    pub secret: Option<String>,
+}
+
+/// Angelegter Webhook; nur hier wird das Geheimnis zum Prüfen der Signatur ausgegeben
+#[derive(Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Eine aktive Ausleihe als JSON-Objekt statt als Tupel
+#[derive(Serialize)]
+pub struct Rental {
+    pub person_id: String,
+    pub car_id: String,
+    // Beginn der Ausleihe in Minuten seit Tag 0 (None bei älteren Ständen)
+    pub started_at: Option<u64>,
+    // Über eine Reservierung übergeben und noch nicht abgeholt: letzter Abholtag
+    pub pickup_due_day: Option<u32>,
+}
+
+// -------------------- Handler --------------------
+
+/// GET /api/persons
+pub async fn list_persons(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<PersonQuery>,
+) -> ApiResult<Page<Person>> {
+    let model = load_state(&pool).await;
+
+    let blocked = match q.status.as_deref() {
+        Some(s) => Some(parse_person_status(s)?),
+        None => None,
+    };
+    let reason = match q.block_reason.as_deref() {
+        Some(r) => Some(parse_block_reason(r)?),
+        None => None,
+    };
+
+    let mut persons: Vec<Person> = model
+        .persons
+        .into_iter()
+        .filter(|p| blocked.is_none_or(|b| p.status.is_blocked() == b))
+        .filter(|p| reason.is_none_or(|r| p.has_block(r)))
+        .filter(|p| q.plan.as_ref().is_none_or(|plan| p.subscription.as_ref().is_some_and(|s| &s.plan == plan)))
+        .filter(|p| q.license_expires_within.is_none_or(|d| p.license_valid_days <= d))
+        .collect();
+
+    match q.sort.as_deref().unwrap_or("identifier") {
+        "identifier" => persons.sort_by(|a, b| a.identifier.cmp(&b.identifier)),
+        "license_valid_days" => persons.sort_by_key(|p| p.license_valid_days),
+        other => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(persons, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/persons/{id}
+pub async fn get_person(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Person>> {
+    let model = load_state(&pool).await;
+    match model.persons.into_iter().find(|p| p.identifier == id) {
+        Some(person) => Ok(Json(Item { data: person })),
+        None => Err(ApiError::not_found(format!("Person '{}' nicht gefunden", id))),
+    }
+}
+
+/// GET /api/persons/{id}/charges
+pub async fn get_person_charges(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Vec<Charge>>> {
+    let model = load_state(&pool).await;
+    if !model.persons.iter().any(|p| p.identifier == id) {
+        return Err(ApiError::not_found(format!("Person '{}' nicht gefunden", id)));
+    }
+    Ok(Json(Item { data: model.get_charges_for_person(&id) }))
+}
+
+/// GET /api/persons/{id}/reservations
+/// Alle Reservierungen der Person mit Status und Verlauf (auch nach dem Abmelden)
+pub async fn get_person_reservations(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Vec<ReservationRecord>>> {
+    let model = load_state(&pool).await;
+    let history = model.get_reservation_history(&id);
+    if history.is_empty() && !model.persons.iter().any(|p| p.identifier == id) {
+        return Err(ApiError::not_found(format!("Person '{}' nicht gefunden", id)));
+    }
+    Ok(Json(Item { data: history }))
+}
+
+/// GET /api/licenses/expiring?within_days=
+/// Personen, die bald gesperrt werden (auch in der Kulanzfrist), die dringendsten zuerst
+pub async fn list_expiring_licenses(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<LicenseQuery>,
+) -> ApiResult<Item<Vec<LicenseExpiry>>> {
+    let model = load_state(&pool).await;
+    let within_days = q.within_days.unwrap_or(DEFAULT_LICENSE_WARN_DAYS);
+    Ok(Json(Item { data: expiring_licenses(&model, within_days) }))
+}
+
+/// GET /api/notifications
+pub async fn list_notifications(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<NotificationQuery>,
+) -> ApiResult<Page<Notification>> {
+    let model = load_state(&pool).await;
+
+    let notifications: Vec<Notification> = model
+        .notifications
+        .into_iter()
+        .filter(|n| q.person_id.as_ref().is_none_or(|id| &n.person_id == id))
+        .filter(|n| q.since_id.is_none_or(|since| n.id > since))
+        .collect();
+
+    paginate(notifications, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/notifications/outbox
+pub async fn list_outbox(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Query(q): Query<OutboxQuery>,
+) -> ApiResult<Page<OutboxEntry>> {
+    require_admin(&headers)?;
+    if let Some(s) = q.status.as_deref() {
+        parse_delivery_status(s)?;
+    }
+    let entries = notifications::load_outbox(&pool, q.status.as_deref(), q.channel.as_deref(), q.person_id.as_deref()).await;
+
+    paginate(entries, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// POST /api/notifications/outbox/{id}/retry
+pub async fn retry_outbox_entry(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> ApiResult<Item<OutboxEntry>> {
+    require_admin(&headers)?;
+    let entry = notifications::load_entry(&pool, id)
+        .await
+        .ok_or_else(|| ApiError::not_found(format!("Postausgang-Eintrag {} nicht gefunden", id)))?;
+    if !notifications::retry(&pool, id).await {
+        return Err(ApiError::bad_request(format!(
+            "Nur fehlgeschlagene Nachrichten können erneut gesendet werden (Status '{}')",
+            entry.status
+        )));
+    }
+    let entry = notifications::load_entry(&pool, id).await.unwrap_or(entry);
+    Ok(Json(Item { data: entry }))
+}
+
+/// GET /api/notifications/{id}/delivery
+pub async fn get_notification_delivery(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<u32>,
+) -> ApiResult<Item<NotificationDelivery>> {
+    let model = load_state(&pool).await;
+    let notification = model
+        .notifications
+        .into_iter()
+        .find(|n| n.id == id)
+        .ok_or_else(|| ApiError::not_found(format!("Nachricht {} nicht gefunden", id)))?;
+
+    // Nach einem Reset können IDs mehrfach im Postausgang stehen
+    let deliveries = notifications::load_outbox(&pool, None, None, Some(&notification.person_id))
+        .await
+        .into_iter()
+        .filter(|e| e.notification_id == id && e.day == notification.day && e.kind == notifications::kind_name(notification.kind))
+        .collect();
+
+    Ok(Json(Item { data: NotificationDelivery { notification, deliveries } }))
+}
+
+/// GET /api/cars
+pub async fn list_cars(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<CarQuery>,
+) -> ApiResult<Page<Car>> {
+    let model = load_state(&pool).await;
+
+    if let Some(s) = q.status.as_deref() {
+        parse_car_status(s)?;
+    }
+    let class = match q.class.as_deref() {
+        Some(c) => Some(parse_car_class(c)?),
+        None => None,
+    };
+
+    let mut cars: Vec<Car> = model
+        .cars
+        .into_iter()
+        .filter(|c| q.status.as_deref().is_none_or(|s| car_status_name(&c.status) == s))
+        .filter(|c| q.min_mileage.is_none_or(|km| c.mileage >= km))
+        .filter(|c| q.max_mileage.is_none_or(|km| c.mileage <= km))
+        .filter(|c| q.station_id.is_none() || c.station_id == q.station_id)
+        .filter(|c| class.is_none_or(|k| c.class == k))
+        .filter(|c| q.min_seats.is_none_or(|seats| c.seats >= seats))
+        .collect();
+
+    match q.sort.as_deref().unwrap_or("identifier") {
+        "identifier" => cars.sort_by(|a, b| a.identifier.cmp(&b.identifier)),
+        "mileage" => cars.sort_by_key(|c| c.mileage),
+        "age_days" => cars.sort_by_key(|c| c.age_days),
+        "rental_count" => cars.sort_by_key(|c| c.rental_count),
+        other => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(cars, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/cars/nearby?lat=&lon=&radius_km=&class=&limit=
+pub async fn nearby_cars(
+    State(index): State<SharedCarIndex>,
+    Query(q): Query<NearbyQuery>,
+) -> ApiResult<Item<Vec<NearbyCar>>> {
+    if !(-90.0..=90.0).contains(&q.lat) || !(-180.0..=180.0).contains(&q.lon) {
+        return Err(ApiError::bad_request("lat/lon außerhalb des gültigen Bereichs"));
+    }
+    let radius_km = q.radius_km.unwrap_or(DEFAULT_RADIUS_KM);
+    if !(radius_km > 0.0 && radius_km <= MAX_RADIUS_KM) {
+        return Err(ApiError::bad_request(format!("radius_km muss zwischen 0 und {} liegen", MAX_RADIUS_KM)));
+    }
+    let class = match q.class.as_deref() {
+        Some(c) => Some(parse_car_class(c)?),
+        None => None,
+    };
+    let limit = q.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let cars = index.read().unwrap().nearby(q.lat, q.lon, radius_km, class, limit);
+    Ok(Json(Item { data: cars }))
+}
+
+/// GET /api/cars/{id}
+pub async fn get_car(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Car>> {
+    let model = load_state(&pool).await;
+    match model.cars.into_iter().find(|c| c.identifier == id) {
+        Some(car) => Ok(Json(Item { data: car })),
+        None => Err(ApiError::not_found(format!("Auto '{}' nicht gefunden", id))),
+    }
+}
+
+/// GET /api/cars/{id}/forecast
+pub async fn get_car_forecast(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<MaintenanceForecast>> {
+    let model = load_state(&pool).await;
+    match model.cars.iter().find(|c| c.identifier == id) {
+        Some(car) => Ok(Json(Item { data: car_forecast(car, model.current_day) })),
+        None => Err(ApiError::not_found(format!("Auto '{}' nicht gefunden", id))),
+    }
+}
+
+/// GET /api/cars/{id}/services
+pub async fn get_service_history(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Vec<ServiceRecord>>> {
+    let model = load_state(&pool).await;
+    let history = model.get_service_history(&id);
+    // Nachweise ausgemusterter/abgemeldeter Autos bleiben abrufbar
+    if history.is_empty() && !model.cars.iter().any(|c| c.identifier == id) {
+        return Err(ApiError::not_found(format!("Auto '{}' nicht gefunden", id)));
+    }
+    Ok(Json(Item { data: history }))
+}
+
+/// GET /api/cars/{id}/damages
+pub async fn get_open_damages(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<Vec<Damage>>> {
+    let model = load_state(&pool).await;
+    if !model.cars.iter().any(|c| c.identifier == id) {
+        return Err(ApiError::not_found(format!("Auto '{}' nicht gefunden", id)));
+    }
+    Ok(Json(Item { data: model.get_open_damages(&id) }))
+}
+
+/// GET /api/forecast
+pub async fn list_forecasts(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<ForecastQuery>,
+) -> ApiResult<Item<Vec<MaintenanceForecast>>> {
+    let model = load_state(&pool).await;
+    let forecasts = match q.due_within_days {
+        Some(days) => due_soon(&model, days),
+        None => fleet_forecast(&model),
+    };
+    Ok(Json(Item { data: forecasts }))
+}
+
+/// GET /api/rebalancing
+/// Vorschau des Umsetzungsplans; ausgeführt wird er im Frontend
+pub async fn get_rebalancing_plan(State(pool): State<SqlitePool>) -> ApiResult<Item<RebalancingPlan>> {
+    let model = load_state(&pool).await;
+    Ok(Json(Item { data: plan_rebalancing(&model) }))
+}
+
+/// GET /api/stations
+pub async fn list_stations(State(pool): State<SqlitePool>) -> ApiResult<Item<Vec<StationInfo>>> {
+    let model = load_state(&pool).await;
+    let stations = model.stations.iter().map(|s| station_info(&model, s)).collect();
+    Ok(Json(Item { data: stations }))
+}
+
+/// GET /api/plans
+pub async fn list_plans(State(pool): State<SqlitePool>) -> ApiResult<Item<Vec<PlanInfo>>> {
+    let model = load_state(&pool).await;
+    let plans = model
+        .plans
+        .iter()
+        .map(|plan| PlanInfo {
+            plan: plan.clone(),
+            subscribers: model
+                .persons
+                .iter()
+                .filter(|p| p.subscription.as_ref().is_some_and(|s| s.plan == plan.name))
+                .count() as u32,
+        })
+        .collect();
+    Ok(Json(Item { data: plans }))
+}
+
+/// GET /api/stations/{id}
+pub async fn get_station(
+    State(pool): State<SqlitePool>,
+    Path(id): Path<String>,
+) -> ApiResult<Item<StationInfo>> {
+    let model = load_state(&pool).await;
+    match model.stations.iter().find(|s| s.identifier == id) {
+        Some(station) => Ok(Json(Item { data: station_info(&model, station) })),
+        None => Err(ApiError::not_found(format!("Station '{}' nicht gefunden", id))),
+    }
+}
+
+/// GET /api/reservations
+pub async fn list_reservations(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<ReservationQuery>,
+) -> ApiResult<Page<Reservation>> {
+    let model = load_state(&pool).await;
+
+    let mut reservations: Vec<Reservation> = model
+        .reservations
+        .into_iter()
+        .filter(|r| q.person_id.as_ref().is_none_or(|id| &r.person_id == id))
+        .filter(|r| q.car_id.as_ref().is_none_or(|id| &r.car_id == id))
+        .collect();
+
+    // Standard: Reihenfolge des Eingangs
+    match q.sort.as_deref() {
+        None => {}
+        Some("priority") => reservations.sort_by_key(|r| r.priority),
+        Some("person_id") => reservations.sort_by(|a, b| a.person_id.cmp(&b.person_id)),
+        Some("car_id") => reservations.sort_by(|a, b| a.car_id.cmp(&b.car_id)),
+        Some(other) => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(reservations, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/reservations/class
+pub async fn list_class_reservations(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<ClassReservationQuery>,
+) -> ApiResult<Page<ClassReservation>> {
+    let model = load_state(&pool).await;
+    let class = match q.class.as_deref() {
+        Some(c) => Some(parse_car_class(c)?),
+        None => None,
+    };
+
+    let mut reservations: Vec<ClassReservation> = model
+        .class_reservations
+        .into_iter()
+        .filter(|r| q.person_id.as_ref().is_none_or(|id| &r.person_id == id))
+        .filter(|r| class.is_none_or(|k| r.filter.class == Some(k)))
+        .collect();
+
+    // Standard: Reihenfolge des Eingangs
+    match q.sort.as_deref() {
+        None => {}
+        Some("id") => reservations.sort_by_key(|r| r.id),
+        Some("priority") => reservations.sort_by_key(|r| r.priority),
+        Some("person_id") => reservations.sort_by(|a, b| a.person_id.cmp(&b.person_id)),
+        Some(other) => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(reservations, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/reservations/history
+pub async fn list_reservation_history(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<ReservationHistoryQuery>,
+) -> ApiResult<Page<ReservationRecord>> {
+    let model = load_state(&pool).await;
+    let state = match q.state.as_deref() {
+        Some(s) => Some(parse_reservation_state(s)?),
+        None => None,
+    };
+
+    let mut records: Vec<ReservationRecord> = model
+        .reservation_history
+        .into_iter()
+        .filter(|r| q.person_id.as_ref().is_none_or(|id| &r.person_id == id))
+        .filter(|r| state.is_none_or(|s| r.state == s))
+        .collect();
+
+    match q.sort.as_deref().unwrap_or("id") {
+        "id" => records.sort_by_key(|r| r.id),
+        "created_day" => records.sort_by_key(|r| (r.created_day, r.id)),
+        other => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(records, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/reservations/events
+pub async fn list_reservation_events(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<ReservationEventQuery>,
+) -> ApiResult<Page<ReservationEvent>> {
+    let model = load_state(&pool).await;
+
+    let events: Vec<ReservationEvent> = model
+        .reservation_events
+        .into_iter()
+        .filter(|e| q.person_id.as_ref().is_none_or(|id| &e.person_id == id))
+        .filter(|e| q.since_id.is_none_or(|since| e.id > since))
+        .collect();
+
+    paginate(events, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/reservations/allocation
+/// Vergleich der Zuteilungsstrategien auf dem aktuellen Zustand (ohne etwas zu vermieten)
+pub async fn get_allocation_comparison(State(pool): State<SqlitePool>) -> ApiResult<Item<Vec<AllocationOutcome>>> {
+    let model = load_state(&pool).await;
+    Ok(Json(Item { data: compare_strategies(&model) }))
+}
+
+/// GET /api/rentals
+pub async fn list_rentals(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<RentalQuery>,
+) -> ApiResult<Page<Rental>> {
+    let model = load_state(&pool).await;
+
+    let mut rentals: Vec<Rental> = model
+        .rentals
+        .iter()
+        .filter(|(p, _)| q.person_id.as_ref().is_none_or(|id| p == id))
+        .filter(|(_, c)| q.car_id.as_ref().is_none_or(|id| c == id))
+        .map(|(person_id, car_id)| Rental {
+            person_id: person_id.clone(),
+            car_id: car_id.clone(),
+            started_at: model.cars.iter().find(|c| &c.identifier == car_id).and_then(|c| c.rented_at),
+            pickup_due_day: model.pickup_due_day(person_id, car_id),
+        })
+        .collect();
+
+    match q.sort.as_deref() {
+        None => {}
+        Some("person_id") => rentals.sort_by(|a, b| a.person_id.cmp(&b.person_id)),
+        Some("car_id") => rentals.sort_by(|a, b| a.car_id.cmp(&b.car_id)),
+        Some(other) => return Err(ApiError::bad_request(format!("Unbekanntes Sortierfeld '{}'", other))),
+    }
+
+    paginate(rentals, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// GET /api/statistics
+pub async fn get_statistics(State(pool): State<SqlitePool>) -> ApiResult<Item<FleetStatistics>> {
+    let model = load_state(&pool).await;
+    Ok(Json(Item { data: fleet_statistics(&model) }))
+}
+
+/// GET /api/metrics/daily?from=&to=
+pub async fn get_daily_metrics(
+    State(pool): State<SqlitePool>,
+    Query(q): Query<DayRangeQuery>,
+) -> ApiResult<Item<Vec<DailySnapshot>>> {
+    let from = q.from.unwrap_or(0);
+    let to = q.to.unwrap_or(u32::MAX);
+    if from > to {
+        return Err(ApiError::bad_request("from darf nicht größer als to sein"));
+    }
+    Ok(Json(Item { data: load_snapshots(&pool, from, to).await }))
+}
+
+/// GET /api/clock
+pub async fn get_clock(
+    State(pool): State<SqlitePool>,
+    State(clock): State<SharedClock>,
+) -> ApiResult<Item<ClockInfo>> {
+    let mut model = load_state(&pool).await;
+    model.minute_of_day = clock.live_minute(model.minute_of_day);
+    let next_day_at = match clock.mode {
+        ClockMode::RealTime => clock.next_midnight().map(|t| t.to_rfc3339()),
+        ClockMode::Simulated => None,
+    };
+    Ok(Json(Item {
+        data: ClockInfo {
+            mode: clock.mode,
+            timezone: clock.timezone.name().to_string(),
+            current_day: model.current_day,
+            minute_of_day: model.minute_of_day,
+            now: model.now(),
+            local_date: clock.today().to_string(),
+            next_day_at,
+        },
+    }))
+}
+
+/// POST /api/scenarios
+/// Rechnet ein Was-wäre-wenn-Szenario auf einer Kopie des Zustands; gespeichert wird nichts.
+pub async fn create_scenario(
+    State(pool): State<SqlitePool>,
+    Json(config): Json<ScenarioConfig>,
+) -> ApiResult<Item<ScenarioReport>> {
+    config.validate().map_err(ApiError::bad_request)?;
+    let model = load_state(&pool).await;
+    if let Some(plan) = config.plan.as_ref().filter(|plan| !model.plans.iter().any(|p| &p.name == *plan)) {
+        return Err(ApiError::bad_request(format!("Unbekannter Tarif '{}'", plan)));
+    }
+    // Mehrere hundert Simulationstage sollen die übrigen Anfragen nicht blockieren
+    let report = tokio::task::spawn_blocking(move || run_scenario(&model, &config))
+        .await
+        .expect("Szenario-Task abgebrochen");
+    Ok(Json(Item { data: report }))
+}
+
+/// GET /api/webhooks
+pub async fn list_webhooks(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Query(q): Query<WebhookQuery>,
+) -> ApiResult<Page<Webhook>> {
+    require_admin(&headers)?;
+    paginate(webhooks::load_webhooks(&pool).await, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// POST /api/webhooks
+pub async fn create_webhook(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Json(body): Json<NewWebhook>,
+) -> ApiResult<Item<CreatedWebhook>> {
+    require_admin(&headers)?;
+    webhooks::parse_http_url(&body.url).map_err(ApiError::bad_request)?;
+    if body.events.is_empty() {
+        return Err(ApiError::bad_request("Mindestens ein Ereignistyp nötig"));
+    }
+    if let Some(unknown) = body.events.iter().find(|e| *e != "*" && !EVENT_TYPES.contains(&e.as_str())) {
+        return Err(ApiError::bad_request(format!("Unbekannter Ereignistyp '{}'", unknown)));
+    }
+    if body.secret.as_ref().is_some_and(|s| s.is_empty()) {
+        return Err(ApiError::bad_request("Geheimnis darf nicht leer sein"));
+    }
+
+    let webhook = webhooks::create_webhook(&pool, &body.url, &body.events, body.secret).await;
+    let secret = webhook.secret.clone();
+    Ok(Json(Item { data: CreatedWebhook { webhook, secret } }))
+}
+
+/// DELETE /api/webhooks/{id}
+pub async fn delete_webhook(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> ApiResult<Item<i64>> {
+    require_admin(&headers)?;
+    if !webhooks::delete_webhook(&pool, id).await {
+        return Err(ApiError::not_found(format!("Webhook {} nicht gefunden", id)));
+    }
+    Ok(Json(Item { data: id }))
+}
+
+/// GET /api/webhooks/{id}/deliveries
+pub async fn list_webhook_deliveries(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    Query(q): Query<WebhookDeliveryQuery>,
+) -> ApiResult<Page<WebhookDelivery>> {
+    require_admin(&headers)?;
+    if webhooks::load_webhook(&pool, id).await.is_none() {
+        return Err(ApiError::not_found(format!("Webhook {} nicht gefunden", id)));
+    }
+    if let Some(s) = q.status.as_deref() {
+        parse_webhook_status(s)?;
+    }
+    let deliveries = webhooks::load_deliveries(&pool, id, q.status.as_deref()).await;
+
+    paginate(deliveries, q.order.as_deref(), q.limit, q.cursor.as_deref())
+}
+
+/// POST /api/webhooks/deliveries/{id}/retry
+pub async fn retry_webhook_delivery(
+    State(pool): State<SqlitePool>,
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+) -> ApiResult<Item<WebhookDelivery>> {
+    require_admin(&headers)?;
+    let delivery = webhooks::load_delivery(&pool, id)
+        .await
+        .ok_or_else(|| ApiError::not_found(format!("Zustellung {} nicht gefunden", id)))?;
+    if !webhooks::retry_delivery(&pool, id).await {
+        return Err(ApiError::bad_request(format!(
+            "Nur aufgegebene Zustellungen können wiederholt werden (Status '{}')",
+            delivery.status
+        )));
+    }
+    let delivery = webhooks::load_delivery(&pool, id).await.unwrap_or(delivery);
+    Ok(Json(Item { data: delivery }))
+}
+
+// -------------------- Hilfsfunktionen --------------------
+
+/// Verwaltungs-Endpunkte verlangen "Authorization: Bearer <ADMIN_TOKEN>".
+/// Ist ADMIN_TOKEN nicht gesetzt, sind sie abgeschaltet.
+fn require_admin(headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = std::env::var("ADMIN_TOKEN").ok().filter(|token| !token.is_empty());
+    check_admin_token(headers, expected.as_deref())
+}
+
+// Ohne Bearer-Token 401, abgeschaltete Verwaltung oder falsches Token 403
+fn check_admin_token(headers: &HeaderMap, expected: Option<&str>) -> Result<(), ApiError> {
+    let Some(expected) = expected else {
+        return Err(ApiError::forbidden("Verwaltung ist abgeschaltet (ADMIN_TOKEN nicht gesetzt)"));
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| ApiError::unauthorized("Admin-Token fehlt (Authorization: Bearer <ADMIN_TOKEN>)"))?;
+    // Vergleich in konstanter Zeit, damit die Laufzeit nichts über das Token verrät
+    let same = given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0;
+    if same {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("Ungültiges Admin-Token"))
+    }
+}
+
+/// Schneidet eine (bereits gefilterte und sortierte) Liste auf eine Seite zu.
+/// Der Cursor ist der Offset des ersten Elements der nächsten Seite.
+fn paginate<T>(
+    mut items: Vec<T>,
+    order: Option<&str>,
+    limit: Option<usize>,
+    cursor: Option<&str>,
+) -> ApiResult<Page<T>> {
+    match order.unwrap_or("asc") {
+        "asc" => {}
+        "desc" => items.reverse(),
+        other => return Err(ApiError::bad_request(format!("Unbekannte Sortierrichtung '{}'", other))),
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(ApiError::bad_request(format!("limit muss zwischen 1 und {} liegen", MAX_LIMIT)));
+    }
+
+    let offset = match cursor {
+        Some(c) => c
+            .parse::<usize>()
+            .map_err(|_| ApiError::bad_request(format!("Ungültiger Cursor '{}'", c)))?,
+        None => 0,
+    };
+
+    let total = items.len();
+    let data: Vec<T> = items.into_iter().skip(offset).take(limit).collect();
+    let next_cursor = if offset + data.len() < total {
+        Some((offset + data.len()).to_string())
+    } else {
+        None
+    };
+
+    Ok(Json(Page { data, meta: PageMeta { total, limit, next_cursor } }))
+}
+
+fn station_info(model: &CarSharing, station: &Station) -> StationInfo {
+    StationInfo {
+        station: station.clone(),
+        occupancy: model.get_station_occupancy(&station.identifier).unwrap_or(0),
+        available_cars: model.get_available_cars_at(&station.identifier),
+    }
+}
+
+fn parse_car_class(s: &str) -> Result<CarClass, ApiError> {
+    match s {
+        "mini" => Ok(CarClass::Mini),
+        "compact" => Ok(CarClass::Compact),
+        "midsize" => Ok(CarClass::Midsize),
+        "suv" => Ok(CarClass::Suv),
+        "van" => Ok(CarClass::Van),
+        other => Err(ApiError::bad_request(format!("Unbekannte Fahrzeugklasse '{}'", other))),
+    }
+}
+
+// true = gesperrt
+fn parse_person_status(s: &str) -> Result<bool, ApiError> {
+    match s {
+        "active" => Ok(false),
+        "blocked" => Ok(true),
+        other => Err(ApiError::bad_request(format!("Unbekannter Personenstatus '{}'", other))),
+    }
+}
+
+fn parse_block_reason(s: &str) -> Result<BlockReason, ApiError> {
+    match s {
+        "license_expired" => Ok(BlockReason::LicenseExpired),
+        "unpaid_invoice" => Ok(BlockReason::UnpaidInvoice),
+        "damage" => Ok(BlockReason::Damage),
+        "manual_suspension" => Ok(BlockReason::ManualSuspension),
+        other => Err(ApiError::bad_request(format!("Unbekannter Sperrgrund '{}'", other))),
+    }
+}
+
+fn parse_reservation_state(s: &str) -> Result<ReservationState, ApiError> {
+    match s {
+        "pending" => Ok(ReservationState::Pending),
+        "fulfilled" => Ok(ReservationState::Fulfilled),
+        "cancelled" => Ok(ReservationState::Cancelled),
+        "expired" => Ok(ReservationState::Expired),
+        "rejected" => Ok(ReservationState::Rejected),
+        "superseded" => Ok(ReservationState::Superseded),
+        "suspended" => Ok(ReservationState::Suspended),
+        "no_show" => Ok(ReservationState::NoShow),
+        other => Err(ApiError::bad_request(format!("Unbekannter Reservierungsstatus '{}'", other))),
+    }
+}
+
+fn parse_delivery_status(s: &str) -> Result<(), ApiError> {
+    match s {
+        "pending" | "sent" | "failed" => Ok(()),
+        other => Err(ApiError::bad_request(format!("Unbekannter Zustellstatus '{}'", other))),
+    }
+}
+
+fn parse_webhook_status(s: &str) -> Result<(), ApiError> {
+    match s {
+        "pending" | "delivered" | "failed" => Ok(()),
+        other => Err(ApiError::bad_request(format!("Unbekannter Zustellstatus '{}'", other))),
+    }
+}
+
+fn parse_car_status(s: &str) -> Result<(), ApiError> {
+    match s {
+        "available" | "rented" | "maintenance" | "tuv" | "repair" | "charging" | "relocating" | "retired" => Ok(()),
+        other => Err(ApiError::bad_request(format!("Unbekannter Autostatus '{}'", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_of(result: Result<(), ApiError>) -> Option<StatusCode> {
+        result.err().map(|e| e.status)
+    }
+
+    fn with_authorization(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn admin_token_missing_is_unauthorized_and_wrong_is_forbidden() {
+        let token = Some("geheim");
+        assert_eq!(status_of(check_admin_token(&HeaderMap::new(), token)), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(status_of(check_admin_token(&with_authorization("Basic geheim"), token)), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(status_of(check_admin_token(&with_authorization("Bearer "), token)), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(status_of(check_admin_token(&with_authorization("Bearer falsch"), token)), Some(StatusCode::FORBIDDEN));
+        assert_eq!(status_of(check_admin_token(&with_authorization("Bearer geheim"), token)), None);
+        // Abgeschaltet bleibt abgeschaltet, auch mit Token
+        assert_eq!(status_of(check_admin_token(&with_authorization("Bearer geheim"), None)), Some(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn unauthorized_response_names_the_bearer_scheme() {
+        let response = ApiError::unauthorized("fehlt").into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        assert!(ApiError::forbidden("falsch").into_response().headers().get(header::WWW_AUTHENTICATE).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Days, NaiveDate, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use sqlx::sqlite::SqlitePool;
+
+use crate::carsharing::{CarSharingService, MINUTES_PER_DAY, MINUTES_PER_HOUR};
+use crate::{load_state, store_state, AppState};
+
+// So oft gleicht der Echtzeit-Task die Uhrzeit mit der Zeitzone ab
+const CLOCK_CHECK_SECS: u64 = 30;
+const DEFAULT_TIMEZONE: &str = "Europe/Berlin";
+
+/// Wer den Geschäftstag weiterschaltet
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockMode {
+    // Von Hand über simulate_n_days (Frontend)
+    Simulated,
+    // Hintergrund-Task nach der Uhrzeit in der eingestellten Zeitzone
+    RealTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct Clock {
+    pub mode: ClockMode,
+    pub timezone: Tz,
+}
+
+pub type SharedClock = Arc<Clock>;
+
+impl Clock {
+    /// Heutiges Datum in der Zeitzone der Uhr
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Minuten seit Mitternacht in der Zeitzone der Uhr
+    pub fn minute_of_day(&self) -> u32 {
+        let now = Utc::now().with_timezone(&self.timezone);
+        now.hour() * MINUTES_PER_HOUR as u32 + now.minute()
+    }
+
+    /// Uhrzeit zu einem gespeicherten Stand: im Echtzeitbetrieb die aktuelle Ortszeit, weil der
+    /// Task reine Uhrzeitänderungen nicht speichert; nie früher als gespeichert (kurz vor Mitternacht)
+    pub fn live_minute(&self, stored: u32) -> u32 {
+        match self.mode {
+            ClockMode::RealTime => stored.max(self.minute_of_day()),
+            ClockMode::Simulated => stored,
+        }
+    }
+
+    /// Beginn des nächsten Tages (None, falls Mitternacht wegen einer Zeitumstellung ausfällt)
+    pub fn next_midnight(&self) -> Option<DateTime<Utc>> {
+        let tomorrow = self.today().checked_add_days(Days::new(1))?;
+        tomorrow
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(self.timezone)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    }
+}
+
+/// Liest die Uhr aus CLOCK_MODE (simulated, realtime) und CLOCK_TIMEZONE (IANA-Name)
+pub fn clock_from_env() -> SharedClock {
+    let mode = match std::env::var("CLOCK_MODE").unwrap_or_default().trim() {
+        "" | "simulated" => ClockMode::Simulated,
+        "realtime" | "real_time" => ClockMode::RealTime,
+        other => panic!("Unbekannter CLOCK_MODE '{}' (simulated oder realtime)", other),
+    };
+    let name = std::env::var("CLOCK_TIMEZONE").unwrap_or_else(|_| DEFAULT_TIMEZONE.to_string());
+    let timezone = name
+        .parse()
+        .unwrap_or_else(|_| panic!("Unbekannte Zeitzone '{}' in CLOCK_TIMEZONE", name));
+    Arc::new(Clock { mode, timezone })
+}
+
+/// Legt die Tabelle für das zuletzt verarbeitete Datum an
+pub async fn create_table(pool: &SqlitePool) {
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS clock_state (
+                id INTEGER PRIMARY KEY NOT NULL,
+                last_date TEXT NOT NULL
+            );
+            "#,
+    )
+    .execute(pool)
+    .await
+    .expect("Tabelle clock_state konnte nicht erstellt werden");
+}
+
+/// Startet im Echtzeitbetrieb den Task, der Uhrzeit und Geschäftstag weiterschaltet
+pub fn spawn_ticker(state: AppState) {
+    if state.clock.mode != ClockMode::RealTime {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(CLOCK_CHECK_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = advance_to_today(&state).await {
+                eprintln!("Tageswechsel fehlgeschlagen: {}", e);
+            }
+        }
+    });
+}
+
+/// Simuliert alle Tage seit dem zuletzt verarbeiteten Datum, auch nach einem Neustart,
+/// und stellt danach die Uhrzeit auf die aktuelle Ortszeit.
+/// Gespeichert wird nur, wenn sich außer der Uhrzeit etwas geändert hat (Tageswechsel, fällige
+/// Reservierungen); sonst würde jeder Takt die Version erhöhen und offene Frontends abweisen.
+/// Beim ersten Lauf werden keine Tage nachgeholt; geht die Systemuhr zurück, passiert nichts.
+/// Liefert die Anzahl der weitergeschalteten Tage.
+pub async fn advance_to_today(state: &AppState) -> Result<u32, sqlx::Error> {
+    let today = state.clock.today();
+    let minute_of_day = state.clock.minute_of_day() as u64;
+    let _guard = state.write_lock.lock().await;
+
+    let last: Option<(String,)> = sqlx::query_as("SELECT last_date FROM clock_state WHERE id = 1")
+        .fetch_optional(&state.pool)
+        .await?;
+    let last = last.and_then(|(date,)| date.parse::<NaiveDate>().ok());
+    let days = match last {
+        Some(last) => (today - last).num_days().max(0) as u32,
+        None => 0,
+    };
+
+    let mut model = load_state(&state.pool).await;
+    let target = (model.current_day + days) as u64 * MINUTES_PER_DAY + minute_of_day;
+    if target > model.now() {
+        let mut unchanged = model.clone();
+        model.advance_minutes(target - model.now());
+        unchanged.minute_of_day = model.minute_of_day;
+        if model != unchanged {
+            store_state(state, &model).await;
+        }
+        if days > 0 {
+            println!("Echtzeituhr: {} Tag(e) verarbeitet, jetzt Tag {} ({}).", days, model.current_day, today);
+        }
+    }
+    if days > 0 || last.is_none() {
+        sqlx::query("INSERT OR REPLACE INTO clock_state (id, last_date) VALUES (1, ?)")
+            .bind(today.to_string())
+            .execute(&state.pool)
+            .await?;
+    }
+    Ok(days)
+}
@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::carsharing::{haversine_km, CarClass, CarSharing, CarSharingService, Station};
+
+// Kantenlänge einer Gitterzelle in Grad (ca. 5.5 km in Nord-Süd-Richtung)
+const CELL_SIZE_DEG: f64 = 0.05;
+const KM_PER_DEG_LAT: f64 = 111.32;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct NearbyCar {
+    pub car_id: String,
+    pub station_id: String,
+    pub class: CarClass,
+    pub distance_km: f64,
+}
+
+#[derive(Clone, Debug)]
+struct IndexedCar {
+    car_id: String,
+    station_id: String,
+    class: CarClass,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Gitter-Index über alle verfügbaren Autos, die an einer Station stehen.
+/// Wird bei jedem Speichern des Zustands neu aufgebaut.
+#[derive(Default)]
+pub struct CarIndex {
+    cells: HashMap<(i32, i32), Vec<IndexedCar>>,
+}
+
+impl CarIndex {
+    pub fn build(cs: &CarSharing) -> Self {
+        let mut cells: HashMap<(i32, i32), Vec<IndexedCar>> = HashMap::new();
+        let available: HashSet<String> = cs.get_available_cars().into_iter().collect();
+        let stations: HashMap<&str, &Station> = cs.stations.iter().map(|s| (s.identifier.as_str(), s)).collect();
+
+        for car in cs.cars.iter().filter(|c| available.contains(&c.identifier)) {
+            let station = match car.station_id.as_deref().and_then(|id| stations.get(id)) {
+                Some(station) => station,
+                None => continue,
+            };
+            cells
+                .entry(cell_of(station.latitude, station.longitude))
+                .or_default()
+                .push(IndexedCar {
+                    car_id: car.identifier.clone(),
+                    station_id: station.identifier.clone(),
+                    class: car.class,
+                    latitude: station.latitude,
+                    longitude: station.longitude,
+                });
+        }
+
+        Self { cells }
+    }
+
+    /// Verfügbare Autos im Umkreis, nach Entfernung sortiert.
+    /// Es werden nur die Gitterzellen durchsucht, die den Umkreis überdecken.
+    pub fn nearby(&self, lat: f64, lon: f64, radius_km: f64, class: Option<CarClass>, limit: usize) -> Vec<NearbyCar> {
+        let d_lat = radius_km / KM_PER_DEG_LAT;
+        // Nahe den Polen wird der Längengrad-Bereich sehr groß, daher begrenzen
+        let d_lon = radius_km / (KM_PER_DEG_LAT * lat.to_radians().cos().max(0.01));
+        let (min_x, min_y) = cell_of(lat - d_lat, lon - d_lon.min(180.0));
+        let (max_x, max_y) = cell_of(lat + d_lat, lon + d_lon.min(180.0));
+
+        let mut found: Vec<NearbyCar> = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let Some(cars) = self.cells.get(&(x, y)) else { continue };
+                for c in cars.iter().filter(|c| class.is_none_or(|k| c.class == k)) {
+                    let distance_km = haversine_km(lat, lon, c.latitude, c.longitude);
+                    if distance_km <= radius_km {
+                        found.push(NearbyCar {
+                            car_id: c.car_id.clone(),
+                            station_id: c.station_id.clone(),
+                            class: c.class,
+                            distance_km,
+                        });
+                    }
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km).then_with(|| a.car_id.cmp(&b.car_id)));
+        found.truncate(limit);
+        found
+    }
+}
+
+fn cell_of(lat: f64, lon: f64) -> (i32, i32) {
+    ((lat / CELL_SIZE_DEG).floor() as i32, (lon / CELL_SIZE_DEG).floor() as i32)
+}
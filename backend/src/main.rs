@@ -1,17 +1,70 @@
+use std::sync::{Arc, RwLock};
+
 use axum::{
-    extract::State,
-    routing::{get, post},
+    extract::{FromRef, State},
+    routing::{delete, get, post},
     Json, Router,
     http::Method,
 };
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions}; // NEU
 use tower_http::cors::{Any, CorsLayer};
 
+pub mod allocation;
+pub mod api;
 pub mod carsharing;
+pub mod clock;
+pub mod forecast;
+pub mod geo;
+pub mod metrics;
+pub mod notifications;
+pub mod rebalancing;
+pub mod scenario;
+pub mod statistics;
+pub mod webhooks;
+use crate::api::ApiError;
 use crate::carsharing::CarSharing;
+use crate::clock::{ClockMode, SharedClock};
+use crate::geo::CarIndex;
+use crate::notifications::SharedChannels;
+
+// Räumlicher Index der verfügbaren Autos, wird bei jedem Speichern erneuert
+pub type SharedCarIndex = Arc<RwLock<CarIndex>>;
+// Hält Frontend und Echtzeituhr davon ab, gleichzeitig den Zustand zu schreiben
+pub type StateWriteLock = Arc<tokio::sync::Mutex<()>>;
 
-// Der AppState ist jetzt der Datenbank-Pool
-type AppState = SqlitePool;
+// Der AppState: Datenbank-Pool plus Caches. Handler holen sich per FromRef nur, was sie brauchen.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub car_index: SharedCarIndex,
+    pub channels: SharedChannels,
+    pub clock: SharedClock,
+    pub write_lock: StateWriteLock,
+}
+
+impl FromRef<AppState> for SqlitePool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedCarIndex {
+    fn from_ref(state: &AppState) -> Self {
+        state.car_index.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedChannels {
+    fn from_ref(state: &AppState) -> Self {
+        state.channels.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedClock {
+    fn from_ref(state: &AppState) -> Self {
+        state.clock.clone()
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -23,6 +76,83 @@ async fn main() {
         .await
         .expect("Kann Datenbank nicht verbinden");
 
+    create_state_table(&pool).await;
+
+    // Ein unlesbarer Zustand (z.B. unbekannter Personenstatus) verhindert den Start, statt jede Anfrage abzubrechen
+    let (stored_json,): (String,) = sqlx::query_as("SELECT state_json FROM carsharing_state WHERE id = 1")
+        .fetch_one(&pool)
+        .await
+        .expect("Gespeicherter State konnte nicht gelesen werden");
+    if let Err(e) = serde_json::from_str::<CarSharing>(&stored_json) {
+        eprintln!("Gespeicherter State ist ungültig: {}", e);
+        std::process::exit(1);
+    }
+
+    metrics::create_table(&pool).await;
+    notifications::create_table(&pool).await;
+    webhooks::create_tables(&pool).await;
+    clock::create_table(&pool).await;
+
+    let channels = notifications::channels_from_env();
+    notifications::spawn_dispatcher(pool.clone(), channels.clone());
+    webhooks::spawn_dispatcher(pool.clone());
+
+    let car_index = Arc::new(RwLock::new(CarIndex::build(&load_state(&pool).await)));
+    let clock = clock::clock_from_env();
+    let state = AppState { pool, car_index, channels, clock, write_lock: Arc::new(tokio::sync::Mutex::new(())) };
+    clock::spawn_ticker(state.clone());
+
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST, Method::DELETE])
+        .allow_headers(vec![axum::http::header::CONTENT_TYPE, axum::http::header::AUTHORIZATION])
+        .allow_origin(Any);
+
+    let app = Router::new()
+        .route("/api/state", get(get_state).post(update_state))
+        .route("/api/persons", get(api::list_persons))
+        .route("/api/persons/{id}", get(api::get_person))
+        .route("/api/persons/{id}/charges", get(api::get_person_charges))
+        .route("/api/persons/{id}/reservations", get(api::get_person_reservations))
+        .route("/api/cars", get(api::list_cars))
+        .route("/api/cars/nearby", get(api::nearby_cars))
+        .route("/api/cars/{id}", get(api::get_car))
+        .route("/api/cars/{id}/forecast", get(api::get_car_forecast))
+        .route("/api/cars/{id}/services", get(api::get_service_history))
+        .route("/api/cars/{id}/damages", get(api::get_open_damages))
+        .route("/api/forecast", get(api::list_forecasts))
+        .route("/api/rebalancing", get(api::get_rebalancing_plan))
+        .route("/api/scenarios", post(api::create_scenario))
+        .route("/api/stations", get(api::list_stations))
+        .route("/api/plans", get(api::list_plans))
+        .route("/api/stations/{id}", get(api::get_station))
+        .route("/api/reservations", get(api::list_reservations))
+        .route("/api/reservations/class", get(api::list_class_reservations))
+        .route("/api/reservations/history", get(api::list_reservation_history))
+        .route("/api/reservations/events", get(api::list_reservation_events))
+        .route("/api/reservations/allocation", get(api::get_allocation_comparison))
+        .route("/api/rentals", get(api::list_rentals))
+        .route("/api/statistics", get(api::get_statistics))
+        .route("/api/licenses/expiring", get(api::list_expiring_licenses))
+        .route("/api/notifications", get(api::list_notifications))
+        .route("/api/notifications/outbox", get(api::list_outbox))
+        .route("/api/notifications/outbox/{id}/retry", post(api::retry_outbox_entry))
+        .route("/api/notifications/{id}/delivery", get(api::get_notification_delivery))
+        .route("/api/metrics/daily", get(api::get_daily_metrics))
+        .route("/api/clock", get(api::get_clock))
+        .route("/api/webhooks", get(api::list_webhooks).post(api::create_webhook))
+        .route("/api/webhooks/{id}", delete(api::delete_webhook))
+        .route("/api/webhooks/{id}/deliveries", get(api::list_webhook_deliveries))
+        .route("/api/webhooks/deliveries/{id}/retry", post(api::retry_webhook_delivery))
+        .with_state(state)
+        .layer(cors);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await.unwrap();
+    println!("Backend lauscht auf http://127.0.0.1:3000 und nutzt SQLite");
+    axum::serve(listener, app).await.unwrap();
+}
+
+/// Legt die Tabelle für den Zustand samt der einen Zeile an, in die gespeichert wird
+async fn create_state_table(pool: &SqlitePool) {
     // --- NEU: Tabelle erstellen, falls sie nicht existiert ---
     sqlx::query(
         r#"
@@ -32,7 +162,7 @@ async fn main() {
             );
             "#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await
     .expect("Tabelle konnte nicht erstellt werden");
 
@@ -42,51 +172,211 @@ async fn main() {
         "INSERT OR IGNORE INTO carsharing_state (id, state_json) VALUES (1, ?);"
     )
     .bind(initial_state_json)
-    .execute(&pool)
+    .execute(pool)
     .await
     .expect("Initialer State konnte nicht eingefügt werden");
-
-
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST])
-        .allow_headers(vec![axum::http::header::CONTENT_TYPE])
-        .allow_origin(Any);
-
-    let app = Router::new()
-        .route("/api/state", get(get_state).post(update_state))
-        .with_state(pool) // Der State ist jetzt der 'pool'
-        .layer(cors);
-
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await.unwrap();
-    println!("Backend lauscht auf http://127.0.0.1:3000 und nutzt SQLite");
-    axum::serve(listener, app).await.unwrap();
 }
 
-/// Liest den Zustand aus der Datenbank
-async fn get_state(State(pool): State<AppState>) -> Json<CarSharing> {
+/// Lädt den gespeicherten Zustand (wird von allen Lese-Endpunkten genutzt)
+pub async fn load_state(pool: &SqlitePool) -> CarSharing {
     let result: (String,) = sqlx::query_as("SELECT state_json FROM carsharing_state WHERE id = 1")
-        .fetch_one(&pool)
+        .fetch_one(pool)
         .await
         .unwrap();
 
     let state_json = result.0;
-    let car_sharing_data: CarSharing = serde_json::from_str(&state_json).unwrap();
+    serde_json::from_str(&state_json).unwrap()
+}
 
-    Json(car_sharing_data)
+/// Liest den Zustand aus der Datenbank; der Verlauf kommt aus der Tabelle daily_snapshots
+async fn get_state(State(pool): State<SqlitePool>, State(clock): State<SharedClock>) -> Json<CarSharing> {
+    let mut model = load_state(&pool).await;
+    model.history = metrics::load_snapshots(&pool, 0, u32::MAX).await;
+    model.minute_of_day = clock.live_minute(model.minute_of_day);
+    Json(model)
 }
 
-/// Schreibt den neuen Zustand in die Datenbank
+/// Schreibt den neuen Zustand vom Frontend in die Datenbank
 async fn update_state(
-    State(pool): State<AppState>,
-    Json(new_car_sharing_state): Json<CarSharing>,
-) {
-    let state_json = serde_json::to_string(&new_car_sharing_state).unwrap();
+    State(state): State<AppState>,
+    Json(mut new_car_sharing_state): Json<CarSharing>,
+) -> Result<(), ApiError> {
+    let _guard = state.write_lock.lock().await;
+
+    // Prioritäten ergeben sich aus dem Tarif und werden nicht vom Frontend übernommen
+    let stored = load_state(&state.pool).await;
+    if new_car_sharing_state.version != stored.version {
+        return Err(ApiError::conflict(format!(
+            "Zustand wurde inzwischen geändert (Version {}, erhalten {}), bitte neu laden",
+            stored.version, new_car_sharing_state.version
+        )));
+    }
+    new_car_sharing_state.enforce_priorities(&stored);
+
+    // Im Echtzeitbetrieb schaltet nur die Uhr Tag und Uhrzeit weiter
+    if state.clock.mode == ClockMode::RealTime {
+        if new_car_sharing_state.current_day != stored.current_day {
+            return Err(ApiError::conflict(format!(
+                "Echtzeitbetrieb: aktueller Tag ist {}, erhalten wurde Tag {}",
+                stored.current_day, new_car_sharing_state.current_day
+            )));
+        }
+        new_car_sharing_state.minute_of_day = state.clock.live_minute(stored.minute_of_day);
+        // Neue Ausleihen beginnen zur Serverzeit, nicht zur Uhrzeit des zuletzt geladenen Stands
+        let now = new_car_sharing_state.now();
+        for car in new_car_sharing_state.cars.iter_mut() {
+            let was_rented = stored.cars.iter().any(|c| c.identifier == car.identifier && c.rented_at.is_some());
+            if car.rented_at.is_some() && !was_rented {
+                car.rented_at = Some(now);
+            }
+        }
+    }
+
+    store_state(&state, &new_car_sharing_state).await;
+    println!("Neuen State vom Frontend empfangen und in die DB geschrieben.");
+    Ok(())
+}
+
+/// Speichert einen Zustand samt Folgearbeiten (Kennzahlen, Postausgang, Webhooks, Index).
+/// Aufrufer halten state.write_lock.
+pub async fn store_state(state: &AppState, new_car_sharing_state: &CarSharing) {
+    let pool = &state.pool;
+    let old_state = load_state(pool).await;
+
+    // Der Tagesverlauf liegt allein in daily_snapshots und wächst nicht im JSON mit
+    let mut blob = new_car_sharing_state.clone();
+    blob.history.clear();
+    blob.version = old_state.version + 1;
+    let state_json = serde_json::to_string(&blob).unwrap();
 
     sqlx::query("UPDATE carsharing_state SET state_json = ? WHERE id = 1")
         .bind(state_json)
-        .execute(&pool)
+        .execute(pool)
         .await
         .unwrap();
 
-    println!("Neuen State vom Frontend empfangen und in die DB geschrieben.");
+    metrics::save_snapshots(pool, new_car_sharing_state).await;
+    notifications::enqueue(pool, new_car_sharing_state, &state.channels).await;
+    let events = webhooks::domain_events(&old_state, new_car_sharing_state);
+    webhooks::publish(pool, new_car_sharing_state.current_day, events).await;
+    *state.car_index.write().unwrap() = CarIndex::build(new_car_sharing_state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carsharing::{Car, CarSharingService, MembershipPlan, Person};
+    use crate::clock::Clock;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    async fn app_state(mode: ClockMode) -> AppState {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        create_state_table(&pool).await;
+        metrics::create_table(&pool).await;
+        notifications::create_table(&pool).await;
+        webhooks::create_tables(&pool).await;
+        clock::create_table(&pool).await;
+        AppState {
+            pool,
+            car_index: Arc::new(RwLock::new(CarIndex::build(&CarSharing::new()))),
+            channels: Arc::new(vec![]),
+            clock: Arc::new(Clock { mode, timezone: chrono_tz::Europe::Berlin }),
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    // Speichert wie das Frontend über POST /api/state und liefert den Statuscode der Antwort
+    async fn post_state(state: &AppState, model: CarSharing) -> StatusCode {
+        match update_state(State(state.clone()), Json(model)).await {
+            Ok(()) => StatusCode::OK,
+            Err(e) => e.into_response().status(),
+        }
+    }
+
+    #[tokio::test]
+    async fn posted_priorities_are_replaced_by_the_plan() {
+        let state = app_state(ClockMode::Simulated).await;
+        let mut model = CarSharing::new();
+        model.register_plan(MembershipPlan {
+            name: "Plus".to_string(),
+            monthly_fee_cents: 0,
+            per_km_cents: 0,
+            per_hour_cents: None,
+            priority: 3,
+            max_reservations: 5,
+            allowed_classes: vec![],
+        });
+        for id in ["p1", "p2"] {
+            model.register_person(Person { license_valid_days: 100, ..Person::new(id) });
+        }
+        model.register_car(Car::new("c1"));
+        model.register_car(Car::new("c2"));
+        model.subscribe_person("p1", "Plus");
+        assert!(model.reserve_car("p1", "c1"));
+        assert_eq!(post_state(&state, model).await, StatusCode::OK);
+
+        // Das Frontend schickt überhöhte Prioritäten für eine bestehende und eine neue Reservierung
+        let mut model = load_state(&state.pool).await;
+        assert!(model.reserve_car("p2", "c2"));
+        for r in model.reservations.iter_mut() {
+            r.priority = 99;
+        }
+        for record in model.reservation_history.iter_mut() {
+            record.priority = 99;
+        }
+        assert_eq!(post_state(&state, model).await, StatusCode::OK);
+
+        let stored = load_state(&state.pool).await;
+        let priorities: Vec<(&str, u32)> = stored.reservations.iter().map(|r| (r.person_id.as_str(), r.priority)).collect();
+        assert_eq!(priorities, vec![("p1", 3), ("p2", 0)]);
+        let recorded: Vec<(&str, u32)> =
+            stored.reservation_history.iter().map(|r| (r.person_id.as_str(), r.priority)).collect();
+        assert_eq!(recorded, vec![("p1", 3), ("p2", 0)]);
+    }
+
+    #[tokio::test]
+    async fn stale_saves_are_rejected() {
+        let state = app_state(ClockMode::Simulated).await;
+        let loaded = load_state(&state.pool).await;
+
+        let mut first = loaded.clone();
+        first.register_car(Car::new("c1"));
+        assert_eq!(post_state(&state, first).await, StatusCode::OK);
+
+        // Ein zweites Fenster mit dem vorher geladenen Stand überschreibt nichts
+        let mut stale = loaded;
+        stale.register_car(Car::new("c2"));
+        assert_eq!(post_state(&state, stale).await, StatusCode::CONFLICT);
+
+        let stored = load_state(&state.pool).await;
+        assert_eq!(stored.version, 1);
+        assert_eq!(stored.get_available_cars(), vec!["c1".to_string()]);
+        assert_eq!(post_state(&state, stored).await, StatusCode::OK);
+        assert_eq!(load_state(&state.pool).await.version, 2);
+    }
+
+    #[tokio::test]
+    async fn realtime_ticks_save_only_real_changes() {
+        let state = app_state(ClockMode::RealTime).await;
+        let loaded = load_state(&state.pool).await;
+
+        // Erster Lauf: nur die Uhrzeit rückt vor, gespeichert wird nichts
+        assert_eq!(clock::advance_to_today(&state).await.unwrap(), 0);
+        assert_eq!(load_state(&state.pool).await.version, 0);
+        assert_eq!(post_state(&state, loaded.clone()).await, StatusCode::OK);
+
+        // Ein nachgeholter Tageswechsel ist eine echte Änderung und macht offene Stände ungültig
+        let loaded = load_state(&state.pool).await;
+        let yesterday = state.clock.today().pred_opt().unwrap();
+        sqlx::query("UPDATE clock_state SET last_date = ? WHERE id = 1")
+            .bind(yesterday.to_string())
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        assert_eq!(clock::advance_to_today(&state).await.unwrap(), 1);
+        let stored = load_state(&state.pool).await;
+        assert_eq!((stored.current_day, stored.version), (1, 2));
+        assert_eq!(post_state(&state, loaded).await, StatusCode::CONFLICT);
+    }
 }
@@ -0,0 +1,172 @@
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::carsharing::CarSharing;
+use crate::statistics::{DailySnapshot, StatusCounts};
+
+/// Legt die Tabelle für die Tageswerte an
+pub async fn create_table(pool: &SqlitePool) {
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS daily_snapshots (
+                day INTEGER PRIMARY KEY NOT NULL,
+                available INTEGER NOT NULL,
+                rented INTEGER NOT NULL,
+                maintenance INTEGER NOT NULL,
+                tuv INTEGER NOT NULL,
+                repair INTEGER NOT NULL DEFAULT 0,
+                charging INTEGER NOT NULL DEFAULT 0,
+                relocating INTEGER NOT NULL DEFAULT 0,
+                retired INTEGER NOT NULL,
+                active_rentals INTEGER NOT NULL,
+                reservations_pending INTEGER NOT NULL,
+                reservations_fulfilled INTEGER NOT NULL,
+                licenses_expired INTEGER NOT NULL,
+                cars_retired INTEGER NOT NULL
+            );
+            "#,
+    )
+    .execute(pool)
+    .await
+    .expect("Tabelle daily_snapshots konnte nicht erstellt werden");
+
+    // Ältere Datenbanken kennen neuere Spalten noch nicht (Fehler = Spalte existiert schon)
+    for column in ["repair", "charging", "relocating"] {
+        let _ = sqlx::query(&format!("ALTER TABLE daily_snapshots ADD COLUMN {} INTEGER NOT NULL DEFAULT 0", column))
+            .execute(pool)
+            .await;
+    }
+}
+
+/// Schreibt neue Tageswerte aus dem Zustand in die Tabelle.
+/// Tage nach `current_day` (z.B. nach einem Reset) werden gelöscht; danach werden
+/// nur Tage nach dem letzten gespeicherten Tag eingefügt.
+pub async fn save_snapshots(pool: &SqlitePool, cs: &CarSharing) {
+    let mut tx = pool.begin().await.unwrap();
+
+    sqlx::query("DELETE FROM daily_snapshots WHERE day > ?")
+        .bind(cs.current_day)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+    let last_day: Option<u32> = sqlx::query_scalar("SELECT MAX(day) FROM daily_snapshots")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+    for s in cs.history.iter().filter(|s| last_day.is_none_or(|last| s.day > last)) {
+        sqlx::query(
+            r#"
+                INSERT OR REPLACE INTO daily_snapshots (
+                    day, available, rented, maintenance, tuv, repair, charging, relocating, retired, active_rentals,
+                    reservations_pending, reservations_fulfilled, licenses_expired, cars_retired
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+                "#,
+        )
+        .bind(s.day)
+        .bind(s.cars.available)
+        .bind(s.cars.rented)
+        .bind(s.cars.maintenance)
+        .bind(s.cars.tuv)
+        .bind(s.cars.repair)
+        .bind(s.cars.charging)
+        .bind(s.cars.relocating)
+        .bind(s.cars.retired)
+        .bind(s.active_rentals)
+        .bind(s.reservations_pending)
+        .bind(s.reservations_fulfilled)
+        .bind(s.licenses_expired)
+        .bind(s.cars_retired)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    }
+
+    tx.commit().await.unwrap();
+}
+
+/// Liest die Tageswerte im Bereich [from, to]
+pub async fn load_snapshots(pool: &SqlitePool, from: u32, to: u32) -> Vec<DailySnapshot> {
+    let rows = sqlx::query("SELECT * FROM daily_snapshots WHERE day >= ? AND day <= ? ORDER BY day")
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+
+    rows.iter()
+        .map(|row| DailySnapshot {
+            day: row.get("day"),
+            cars: StatusCounts {
+                available: row.get("available"),
+                rented: row.get("rented"),
+                maintenance: row.get("maintenance"),
+                tuv: row.get("tuv"),
+                repair: row.get("repair"),
+                charging: row.get("charging"),
+                relocating: row.get("relocating"),
+                retired: row.get("retired"),
+            },
+            active_rentals: row.get("active_rentals"),
+            reservations_pending: row.get("reservations_pending"),
+            reservations_fulfilled: row.get("reservations_fulfilled"),
+            licenses_expired: row.get("licenses_expired"),
+            cars_retired: row.get("cars_retired"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        create_table(&pool).await;
+        pool
+    }
+
+    // Tageswert, an dem sich Durchläufe unterscheiden lassen (Zahl der Ausleihen)
+    fn snapshot(day: u32, active_rentals: u32) -> DailySnapshot {
+        DailySnapshot {
+            day,
+            cars: StatusCounts::default(),
+            active_rentals,
+            reservations_pending: 0,
+            reservations_fulfilled: 0,
+            licenses_expired: 0,
+            cars_retired: 0,
+        }
+    }
+
+    fn state(current_day: u32, history: Vec<DailySnapshot>) -> CarSharing {
+        let mut cs = CarSharing::new();
+        cs.current_day = current_day;
+        cs.history = history;
+        cs
+    }
+
+    async fn rentals_per_day(pool: &SqlitePool) -> Vec<(u32, u32)> {
+        load_snapshots(pool, 0, u32::MAX).await.iter().map(|s| (s.day, s.active_rentals)).collect()
+    }
+
+    #[tokio::test]
+    async fn rewind_drops_later_days_and_resave_adds_no_duplicates() {
+        let pool = memory_pool().await;
+        let first_run = vec![snapshot(1, 1), snapshot(2, 2), snapshot(3, 3)];
+        save_snapshots(&pool, &state(3, first_run.clone())).await;
+        // Erneutes Speichern desselben Stands ändert nichts
+        save_snapshots(&pool, &state(3, first_run)).await;
+        assert_eq!(rentals_per_day(&pool).await, vec![(1, 1), (2, 2), (3, 3)]);
+
+        // Zurück auf Tag 2: Tag 3 des alten Laufs verschwindet
+        save_snapshots(&pool, &state(2, vec![snapshot(1, 1), snapshot(2, 2)])).await;
+        assert_eq!(rentals_per_day(&pool).await, vec![(1, 1), (2, 2)]);
+
+        // Der neu simulierte Tag 3 ersetzt den alten
+        save_snapshots(&pool, &state(3, vec![snapshot(1, 1), snapshot(2, 2), snapshot(3, 7)])).await;
+        assert_eq!(rentals_per_day(&pool).await, vec![(1, 1), (2, 2), (3, 7)]);
+    }
+}
@@ -0,0 +1,555 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+
+use crate::carsharing::{CarSharing, NotificationKind};
+
+// Abstand zwischen zwei Durchläufen des Versands
+const DISPATCH_INTERVAL_SECS: u64 = 5;
+// Höchstens so viele Nachrichten pro Durchlauf
+const DISPATCH_BATCH: i64 = 50;
+// Nach so vielen Fehlversuchen gilt eine Nachricht als nicht zustellbar
+pub const MAX_ATTEMPTS: u32 = 5;
+// Wartezeit vor dem n-ten erneuten Versuch: RETRY_BASE_SECS * 2^(n-1)
+const RETRY_BASE_SECS: i64 = 30;
+const SMTP_TIMEOUT_SECS: u64 = 10;
+
+/// Alle eingerichteten Kanäle; jede Nachricht geht über jeden Kanal
+pub type SharedChannels = Arc<Vec<Arc<dyn NotificationChannel>>>;
+
+/// Eine Nachricht im Postausgang, je Kanal ein Eintrag
+#[derive(Clone, Debug, Serialize)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub notification_id: u32,
+    pub channel: String,
+    pub person_id: String,
+    // E-Mail-Adresse der Person beim Einstellen (None = keine hinterlegt)
+    pub email: Option<String>,
+    pub kind: String,
+    pub day: u32,
+    pub message: String,
+    // pending, sent oder failed
+    pub status: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    // Unix-Zeit in Sekunden
+    pub next_attempt_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+/// Ein Weg, auf dem Nachrichten die Personen erreichen.
+/// `send` darf blockieren, der Versand ruft es in `spawn_blocking` auf.
+pub trait NotificationChannel: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn send(&self, entry: &OutboxEntry) -> Result<(), String>;
+
+    /// Prüft vor dem Versand, ob der Eintrag über diesen Kanal überhaupt zustellbar ist.
+    /// Ein Fehler markiert den Eintrag sofort als failed, ohne weitere Versuche.
+    fn deliverable(&self, _entry: &OutboxEntry) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Hängt jede Nachricht als Zeile an eine Datei an
+pub struct LogChannel {
+    pub path: PathBuf,
+}
+
+impl NotificationChannel for LogChannel {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn send(&self, entry: &OutboxEntry) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("{} kann nicht geöffnet werden: {}", self.path.display(), e))?;
+        writeln!(file, "Tag {} | {} | {} | {} | {}", entry.day, entry.person_id, entry.kind, subject(&entry.kind), entry.message)
+            .map_err(|e| format!("Schreiben nach {} fehlgeschlagen: {}", self.path.display(), e))
+    }
+}
+
+/// Versendet E-Mails über einen SMTP-Server (ohne TLS und Anmeldung, z.B. ein lokales Relay)
+pub struct SmtpChannel {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+}
+
+impl NotificationChannel for SmtpChannel {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    fn deliverable(&self, entry: &OutboxEntry) -> Result<(), String> {
+        match entry.email {
+            Some(_) => Ok(()),
+            None => Err(format!("Für '{}' ist keine E-Mail-Adresse hinterlegt", entry.person_id)),
+        }
+    }
+
+    fn send(&self, entry: &OutboxEntry) -> Result<(), String> {
+        self.deliverable(entry)?;
+        let to = entry.email.as_deref().unwrap_or_default();
+        let stream = TcpStream::connect((self.host.as_str(), self.port))
+            .map_err(|e| format!("Verbindung zu {}:{} fehlgeschlagen: {}", self.host, self.port, e))?;
+        let timeout = Some(Duration::from_secs(SMTP_TIMEOUT_SECS));
+        stream.set_read_timeout(timeout).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(timeout).map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut writer = stream;
+
+        expect_reply(&mut reader, 2)?;
+        smtp_command(&mut writer, &mut reader, "HELO carsharing", 2)?;
+        smtp_command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", self.from), 2)?;
+        smtp_command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", to), 2)?;
+        smtp_command(&mut writer, &mut reader, "DATA", 3)?;
+
+        let mut data = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+            self.from,
+            to,
+            encode_header(subject(&entry.kind)),
+        );
+        for line in entry.message.lines() {
+            // Zeilen mit führendem Punkt verdoppeln, sonst endet DATA zu früh
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        smtp_command(&mut writer, &mut reader, &format!("{}.", data), 2)?;
+        // Die Nachricht ist angenommen; ein Fehler beim Abmelden ändert daran nichts
+        let _ = smtp_command(&mut writer, &mut reader, "QUIT", 2);
+        Ok(())
+    }
+}
+
+/// Kanäle aus Umgebungsvariablen:
+/// NOTIFY_CHANNELS (z.B. "log,smtp", Standard "log"), NOTIFY_LOG_FILE,
+/// SMTP_HOST, SMTP_PORT, SMTP_FROM
+pub fn channels_from_env() -> SharedChannels {
+    let var = |name: &str, default: &str| std::env::var(name).unwrap_or_else(|_| default.to_string());
+    let mut channels: Vec<Arc<dyn NotificationChannel>> = Vec::new();
+
+    for name in var("NOTIFY_CHANNELS", "log").split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match name {
+            "log" => channels.push(Arc::new(LogChannel { path: PathBuf::from(var("NOTIFY_LOG_FILE", "data/notifications.log")) })),
+            "smtp" => channels.push(Arc::new(SmtpChannel {
+                host: var("SMTP_HOST", "127.0.0.1"),
+                port: var("SMTP_PORT", "25").parse().expect("SMTP_PORT muss eine Portnummer sein"),
+                from: var("SMTP_FROM", "carsharing@localhost"),
+            })),
+            other => eprintln!("Unbekannter Benachrichtigungskanal '{}' wird ignoriert", other),
+        }
+    }
+    Arc::new(channels)
+}
+
+/// Legt die Tabelle für den Postausgang an
+pub async fn create_table(pool: &SqlitePool) {
+    // Nach einem Reset beginnen die Nachrichten-IDs wieder bei 1,
+    // deshalb gehören Tag, Person und Art mit zum Schlüssel
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS notification_outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                notification_id INTEGER NOT NULL,
+                channel TEXT NOT NULL,
+                person_id TEXT NOT NULL,
+                email TEXT,
+                kind TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                message TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                next_attempt_at INTEGER NOT NULL,
+                sent_at INTEGER,
+                UNIQUE (notification_id, day, person_id, kind, channel)
+            );
+            "#,
+    )
+    .execute(pool)
+    .await
+    .expect("Tabelle notification_outbox konnte nicht erstellt werden");
+
+    // Ältere Datenbanken kennen die Spalte noch nicht (Fehler = Spalte existiert schon)
+    let _ = sqlx::query("ALTER TABLE notification_outbox ADD COLUMN email TEXT").execute(pool).await;
+}
+
+/// Stellt neue Nachrichten aus dem Zustand für alle Kanäle in den Postausgang.
+/// Neu sind Nachrichten mit einer höheren ID als die höchste im Postausgang.
+/// Bereits vorhandene Einträge bleiben unverändert.
+pub async fn enqueue(pool: &SqlitePool, cs: &CarSharing, channels: &SharedChannels) {
+    let mut tx = pool.begin().await.unwrap();
+    let now = unix_now();
+
+    let last_id: Option<u32> = sqlx::query_scalar("SELECT MAX(notification_id) FROM notification_outbox")
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+    // Nach einem Reset beginnen die IDs wieder bei 1; dann werden alle geprüft (UNIQUE verhindert Doppelte)
+    let newest = cs.notifications.iter().map(|n| n.id).max().unwrap_or(0);
+    let after = match last_id {
+        Some(last) if last <= newest => last,
+        _ => 0,
+    };
+
+    for n in cs.notifications.iter().filter(|n| n.id > after) {
+        let email = cs.persons.iter().find(|p| p.identifier == n.person_id).and_then(|p| p.email.clone());
+        for channel in channels.iter() {
+            sqlx::query(
+                r#"
+                    INSERT OR IGNORE INTO notification_outbox (
+                        notification_id, channel, person_id, email, kind, day, message, next_attempt_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?);
+                    "#,
+            )
+            .bind(n.id)
+            .bind(channel.name())
+            .bind(&n.person_id)
+            .bind(&email)
+            .bind(kind_name(n.kind))
+            .bind(n.day)
+            .bind(&n.message)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+    }
+
+    tx.commit().await.unwrap();
+}
+
+/// Startet den Versand im Hintergrund
+pub fn spawn_dispatcher(pool: SqlitePool, channels: SharedChannels) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(DISPATCH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = dispatch_due(&pool, &channels).await {
+                eprintln!("Versand der Benachrichtigungen fehlgeschlagen: {}", e);
+            }
+        }
+    });
+}
+
+/// Ein Durchlauf: versucht alle fälligen Nachrichten zuzustellen.
+/// Fehlgeschlagene Versuche werden mit wachsendem Abstand wiederholt,
+/// nach MAX_ATTEMPTS Versuchen wird die Nachricht als failed markiert.
+pub async fn dispatch_due(pool: &SqlitePool, channels: &SharedChannels) -> Result<u32, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM notification_outbox WHERE status = 'pending' AND next_attempt_at <= ? ORDER BY id LIMIT ?")
+        .bind(unix_now())
+        .bind(DISPATCH_BATCH)
+        .fetch_all(pool)
+        .await?;
+
+    let mut delivered = 0;
+    for entry in rows.iter().map(entry_from_row) {
+        let channel = channels.iter().find(|c| c.name() == entry.channel);
+        if let Some(Err(error)) = channel.map(|c| c.deliverable(&entry)) {
+            sqlx::query("UPDATE notification_outbox SET status = 'failed', last_error = ? WHERE id = ?")
+                .bind(error)
+                .bind(entry.id)
+                .execute(pool)
+                .await?;
+            continue;
+        }
+
+        let result = match channel {
+            Some(channel) => {
+                let channel = channel.clone();
+                let job = entry.clone();
+                tokio::task::spawn_blocking(move || channel.send(&job))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+            }
+            None => Err(format!("Kanal '{}' ist nicht eingerichtet", entry.channel)),
+        };
+
+        let attempts = entry.attempts + 1;
+        let now = unix_now();
+        match result {
+            Ok(()) => {
+                delivered += 1;
+                sqlx::query("UPDATE notification_outbox SET status = 'sent', attempts = ?, last_error = NULL, sent_at = ? WHERE id = ?")
+                    .bind(attempts)
+                    .bind(now)
+                    .bind(entry.id)
+                    .execute(pool)
+                    .await?;
+            }
+            Err(error) => {
+                let status = if attempts >= MAX_ATTEMPTS { "failed" } else { "pending" };
+                sqlx::query("UPDATE notification_outbox SET status = ?, attempts = ?, last_error = ?, next_attempt_at = ? WHERE id = ?")
+                    .bind(status)
+                    .bind(attempts)
+                    .bind(error)
+                    .bind(now + (RETRY_BASE_SECS << (attempts - 1).min(16)))
+                    .bind(entry.id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// Einträge des Postausgangs, optional gefiltert, nach ID sortiert
+pub async fn load_outbox(pool: &SqlitePool, status: Option<&str>, channel: Option<&str>, person_id: Option<&str>) -> Vec<OutboxEntry> {
+    let rows = sqlx::query(
+        r#"
+            SELECT * FROM notification_outbox
+            WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR channel = ?2) AND (?3 IS NULL OR person_id = ?3)
+            ORDER BY id
+            "#,
+    )
+    .bind(status)
+    .bind(channel)
+    .bind(person_id)
+    .fetch_all(pool)
+    .await
+    .unwrap();
+
+    rows.iter().map(entry_from_row).collect()
+}
+
+pub async fn load_entry(pool: &SqlitePool, id: i64) -> Option<OutboxEntry> {
+    sqlx::query("SELECT * FROM notification_outbox WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+        .map(|row| entry_from_row(&row))
+}
+
+/// Setzt eine fehlgeschlagene Nachricht zurück, damit sie erneut versendet wird
+pub async fn retry(pool: &SqlitePool, id: i64) -> bool {
+    let result = sqlx::query("UPDATE notification_outbox SET status = 'pending', attempts = 0, next_attempt_at = ? WHERE id = ? AND status = 'failed'")
+        .bind(unix_now())
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    result.rows_affected() == 1
+}
+
+/// Name der Nachrichtenart in der Tabelle und in der API
+pub fn kind_name(kind: NotificationKind) -> &'static str {
+    match kind {
+        NotificationKind::LicenseExpiring => "license_expiring",
+        NotificationKind::LicenseGracePeriod => "license_grace_period",
+        NotificationKind::LicenseExpired => "license_expired",
+        NotificationKind::ReservationFulfilled => "reservation_fulfilled",
+        NotificationKind::ReservationExpired => "reservation_expired",
+        NotificationKind::CarDueBack => "car_due_back",
+        NotificationKind::Invoice => "invoice",
+        NotificationKind::NoShow => "no_show",
+    }
+}
+
+fn subject(kind: &str) -> &'static str {
+    match kind {
+        "license_expiring" => "Ihr Führerschein läuft bald ab",
+        "license_grace_period" => "Ihr Führerschein ist abgelaufen",
+        "license_expired" => "Ihr Konto wurde gesperrt",
+        "reservation_fulfilled" => "Ihre Reservierung ist erfüllt",
+        "reservation_expired" => "Ihre Reservierung ist verfallen",
+        "car_due_back" => "Rückgabe fällig",
+        "invoice" => "Neue Rechnung",
+        "no_show" => "Reserviertes Auto nicht genutzt",
+        _ => "Carsharing",
+    }
+}
+
+fn entry_from_row(row: &SqliteRow) -> OutboxEntry {
+    OutboxEntry {
+        id: row.get("id"),
+        notification_id: row.get("notification_id"),
+        channel: row.get("channel"),
+        person_id: row.get("person_id"),
+        email: row.get("email"),
+        kind: row.get("kind"),
+        day: row.get("day"),
+        message: row.get("message"),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        sent_at: row.get("sent_at"),
+    }
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+// Liest eine (ggf. mehrzeilige) SMTP-Antwort und prüft die erste Ziffer des Codes
+fn expect_reply(reader: &mut impl BufRead, class: u8) -> Result<(), String> {
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).map_err(|e| format!("SMTP-Antwort fehlt: {}", e))?;
+        if read == 0 || line.len() < 4 {
+            return Err(format!("Unerwartete SMTP-Antwort '{}'", line.trim_end()));
+        }
+        // "250-..." kündigt weitere Zeilen an, "250 ..." ist die letzte
+        if line.as_bytes()[3] == b'-' {
+            continue;
+        }
+        return if line.as_bytes()[0] == b'0' + class {
+            Ok(())
+        } else {
+            Err(format!("SMTP-Server meldet '{}'", line.trim_end()))
+        };
+    }
+}
+
+fn smtp_command(writer: &mut impl Write, reader: &mut impl BufRead, command: &str, class: u8) -> Result<(), String> {
+    write!(writer, "{}\r\n", command).map_err(|e| format!("Senden an SMTP-Server fehlgeschlagen: {}", e))?;
+    writer.flush().map_err(|e| e.to_string())?;
+    expect_reply(reader, class)
+}
+
+// Kopfzeilen dürfen nur ASCII enthalten, Umlaute werden nach RFC 2047 (Q-Encoding) kodiert
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut encoded = String::from("=?UTF-8?Q?");
+    for byte in text.bytes() {
+        match byte {
+            b' ' => encoded.push('_'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("={:02X}", byte)),
+        }
+    }
+    encoded.push_str("?=");
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Minimaler SMTP-Server für eine Sitzung; liefert alle empfangenen Zeilen.
+    // Mit reject_rcpt wird der Empfänger abgelehnt.
+    async fn smtp_stub(listener: TcpListener, reject_rcpt: bool) -> Vec<String> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut lines = tokio::io::BufReader::new(read).lines();
+        let mut received = Vec::new();
+        let mut in_data = false;
+
+        write.write_all(b"220 stub ESMTP\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            received.push(line.clone());
+            let reply: &[u8] = if in_data {
+                if line != "." {
+                    continue;
+                }
+                in_data = false;
+                b"250 queued\r\n"
+            } else if line.starts_with("HELO") {
+                b"250-stub\r\n250 ok\r\n"
+            } else if line.starts_with("RCPT") && reject_rcpt {
+                b"550 no such user\r\n"
+            } else if line == "DATA" {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if line == "QUIT" {
+                b"221 bye\r\n"
+            } else {
+                b"250 ok\r\n"
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        received
+    }
+
+    async fn start_stub(reject_rcpt: bool) -> (SmtpChannel, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let channel = SmtpChannel {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            from: "carsharing@example.org".to_string(),
+        };
+        (channel, tokio::spawn(smtp_stub(listener, reject_rcpt)))
+    }
+
+    fn entry(email: Option<&str>, kind: &str, message: &str) -> OutboxEntry {
+        OutboxEntry {
+            id: 1,
+            notification_id: 1,
+            channel: "smtp".to_string(),
+            person_id: "anna".to_string(),
+            email: email.map(str::to_string),
+            kind: kind.to_string(),
+            day: 3,
+            message: message.to_string(),
+            status: "pending".to_string(),
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: 0,
+            sent_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn smtp_channel_delivers_to_stub() {
+        let (channel, server) = start_stub(false).await;
+        let job = entry(Some("anna@example.org"), "license_expiring", "Hallo Anna\n.Zeile mit Punkt");
+        let result = tokio::task::spawn_blocking(move || channel.send(&job)).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(received[0], "HELO carsharing");
+        assert_eq!(received[1], "MAIL FROM:<carsharing@example.org>");
+        assert_eq!(received[2], "RCPT TO:<anna@example.org>");
+        assert_eq!(received[3], "DATA");
+        assert!(received.contains(&"To: <anna@example.org>".to_string()));
+        assert!(received.contains(&"Subject: =?UTF-8?Q?Ihr_F=C3=BChrerschein_l=C3=A4uft_bald_ab?=".to_string()));
+        assert!(received.contains(&"Hallo Anna".to_string()));
+        // Führender Punkt wird verdoppelt, damit DATA nicht vorzeitig endet
+        assert!(received.contains(&"..Zeile mit Punkt".to_string()));
+        assert_eq!(received.last().map(String::as_str), Some("QUIT"));
+    }
+
+    #[tokio::test]
+    async fn smtp_channel_reports_rejected_recipient() {
+        let (channel, server) = start_stub(true).await;
+        let job = entry(Some("niemand@example.org"), "invoice", "Rechnung");
+        let result = tokio::task::spawn_blocking(move || channel.send(&job)).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(result, Err("SMTP-Server meldet '550 no such user'".to_string()));
+        assert!(!received.contains(&"DATA".to_string()));
+    }
+
+    #[tokio::test]
+    async fn smtp_channel_needs_email_address() {
+        let channel = SmtpChannel {
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            from: "carsharing@example.org".to_string(),
+        };
+        let job = entry(None, "invoice", "Rechnung");
+
+        assert!(channel.deliverable(&job).is_err());
+        assert_eq!(channel.send(&job), Err("Für 'anna' ist keine E-Mail-Adresse hinterlegt".to_string()));
+    }
+}
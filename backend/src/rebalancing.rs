@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::carsharing::{station_distance_km, Car, CarFilter, CarSharing, CarSharingService};
+
+/// Angebot und Nachfrage an einer Station.
+/// Nachfrage = Reservierungen nach Merkmalen für diese Station. Reservierungen für ein
+/// bestimmtes Auto zählen nicht mit, weil dieses Auto ohnehin schon an seiner Station steht.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StationBalance {
+    pub station_id: String,
+    pub capacity: u32,
+    pub occupancy: u32,
+    pub available: u32,
+    pub demand: u32,
+    // Reservierungen, für die an der Station kein passendes Auto bereitsteht
+    pub shortfall: u32,
+    // Verfügbare Autos ohne eigene Reservierung, die vor Ort niemand braucht und abgegeben werden können
+    pub surplus: u32,
+}
+
+/// Vorgeschlagene Umsetzung eines Autos
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RelocationMove {
+    pub car_id: String,
+    pub from_station: String,
+    pub to_station: String,
+    pub distance_km: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RebalancingPlan {
+    pub stations: Vec<StationBalance>,
+    pub moves: Vec<RelocationMove>,
+    pub unmet_before: u32,
+    pub unmet_after: u32,
+}
+
+/// Angebot und Nachfrage aller Stationen
+pub fn station_balances(cs: &CarSharing) -> Vec<StationBalance> {
+    cs.stations
+        .iter()
+        .map(|s| {
+            let available = cs.get_available_cars_at(&s.identifier);
+            let demand = cs
+                .class_reservations
+                .iter()
+                .filter(|r| r.filter.station_id.as_deref() == Some(s.identifier.as_str()))
+                .count() as u32;
+            let (unmet, spare) = local_match(cs, &s.identifier);
+
+            StationBalance {
+                station_id: s.identifier.clone(),
+                capacity: s.capacity,
+                occupancy: cs.get_station_occupancy(&s.identifier).unwrap_or(0),
+                available: available.len() as u32,
+                demand,
+                shortfall: unmet.len() as u32,
+                surplus: spare.len() as u32,
+            }
+        })
+        .collect()
+}
+
+/// Schlägt Umsetzungen vor, die möglichst viele offene Reservierungen bedienen.
+/// Greedy: alle Paare aus abgebbarem Auto und Station mit einer offenen Reservierung, deren Merkmale
+/// das Auto erfüllt, werden nach Entfernung sortiert und der Reihe nach vergeben, solange am Ziel
+/// eine passende Reservierung offen und Platz frei ist.
+pub fn plan_rebalancing(cs: &CarSharing) -> RebalancingPlan {
+    let stations = station_balances(cs);
+    let matches: Vec<(Vec<&CarFilter>, Vec<&Car>)> = stations.iter().map(|b| local_match(cs, &b.station_id)).collect();
+
+    let mut candidates: Vec<(f64, &Car, usize, usize)> = Vec::new();
+    for (from, (_, spare)) in matches.iter().enumerate() {
+        for (to, (unmet, _)) in matches.iter().enumerate() {
+            let distance_km = match station_distance_km(&cs.stations, &stations[from].station_id, &stations[to].station_id) {
+                Some(d) if from != to => d,
+                _ => continue,
+            };
+            for car in spare.iter().filter(|car| unmet.iter().any(|f| matches_elsewhere(f, car))) {
+                candidates.push((distance_km, car, from, to));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.identifier.cmp(&b.1.identifier)));
+
+    let mut unmet: Vec<Vec<&CarFilter>> = matches.iter().map(|(unmet, _)| unmet.clone()).collect();
+    let mut free_slots: Vec<u32> = stations.iter().map(|b| b.capacity.saturating_sub(b.occupancy)).collect();
+    let mut moves: Vec<RelocationMove> = Vec::new();
+
+    for (distance_km, car, from, to) in candidates {
+        if free_slots[to] == 0 || moves.iter().any(|m| m.car_id == car.identifier) {
+            continue;
+        }
+        let wanted = match unmet[to].iter().position(|f| matches_elsewhere(f, car)) {
+            Some(index) => index,
+            None => continue,
+        };
+        unmet[to].remove(wanted);
+        free_slots[to] -= 1;
+        // Der Platz an der Ausgangsstation wird erst nach der Ausführung frei
+        moves.push(RelocationMove {
+            car_id: car.identifier.clone(),
+            from_station: stations[from].station_id.clone(),
+            to_station: stations[to].station_id.clone(),
+            distance_km,
+        });
+    }
+
+    let unmet_before = stations.iter().map(|b| b.shortfall).sum();
+    RebalancingPlan {
+        stations,
+        unmet_after: unmet.iter().map(|u| u.len() as u32).sum(),
+        unmet_before,
+        moves,
+    }
+}
+
+/// Führt einen Plan aus. Liefert die Anzahl der gestarteten Umsetzungen;
+/// Züge, die inzwischen nicht mehr möglich sind, werden übersprungen.
+pub fn apply_rebalancing(cs: &mut CarSharing, plan: &RebalancingPlan) -> u32 {
+    let mut started = 0;
+    for m in plan.moves.iter() {
+        let still_there = cs
+            .cars
+            .iter()
+            .any(|c| c.identifier == m.car_id && c.station_id.as_deref() == Some(m.from_station.as_str()));
+        if still_there && cs.relocate_car(&m.car_id, &m.to_station) {
+            started += 1;
+        }
+    }
+    started
+}
+
+fn is_reserved(cs: &CarSharing, car_id: &str) -> bool {
+    !cs.get_reservations_for_car(car_id).is_empty()
+}
+
+// Ordnet die Reservierungen nach Merkmalen einer Station den freien Autos vor Ort zu.
+// Liefert die Merkmale der Reservierungen ohne passendes Auto und die übrigen freien Autos.
+fn local_match<'a>(cs: &'a CarSharing, station_id: &str) -> (Vec<&'a CarFilter>, Vec<&'a Car>) {
+    let mut spare: Vec<&Car> = cs
+        .get_available_cars_at(station_id)
+        .iter()
+        .filter(|id| !is_reserved(cs, id))
+        .filter_map(|id| cs.cars.iter().find(|c| &c.identifier == id))
+        .collect();
+    let mut unmet: Vec<&CarFilter> = Vec::new();
+
+    for r in cs.class_reservations.iter().filter(|r| r.filter.station_id.as_deref() == Some(station_id)) {
+        match spare.iter().position(|car| r.filter.matches(car)) {
+            Some(index) => {
+                spare.remove(index);
+            }
+            None => unmet.push(&r.filter),
+        }
+    }
+    (unmet, spare)
+}
+
+// Erfüllt das Auto die Merkmale, wenn es an die gewünschte Station umgesetzt wird?
+fn matches_elsewhere(filter: &CarFilter, car: &Car) -> bool {
+    CarFilter { station_id: None, ..filter.clone() }.matches(car)
+}
@@ -0,0 +1,470 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::carsharing::{
+    Car, CarClass, CarFilter, CarSharing, CarSharingService, CarStatus, FleetPolicy, LicenseClass, Person,
+    ReservationState, ReturnDetails, DAYS_PER_YEAR, MINUTES_PER_HOUR,
+};
+use crate::statistics::status_counts;
+
+// Obergrenzen, damit eine Anfrage das Backend nicht minutenlang beschäftigt
+pub const MAX_SCENARIO_DAYS: u32 = 365;
+pub const MAX_REQUESTS_PER_DAY: f64 = 200.0;
+pub const MAX_EXTRA_CARS: u32 = 500;
+pub const MAX_PERSONS: u32 = 5000;
+
+const HOURS_PER_DAY: u32 = 24;
+// Alter der synthetischen Personen, damit Mindestalter nie greift
+const SYNTHETIC_AGE_YEARS: i64 = 30;
+
+/// Gleichverteilter Bereich, beide Grenzen eingeschlossen
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ValueRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl ValueRange {
+    fn sample(self, rng: &mut StdRng) -> u32 {
+        rng.gen_range(self.min..=self.max)
+    }
+}
+
+/// Auf welchem Zustand das Szenario aufsetzt
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioBase {
+    // Kopie des gespeicherten Zustands (Flotte, Personen, offene Reservierungen)
+    #[default]
+    Current,
+    // Leere Flotte, nur die Autos aus extra_cars
+    Empty,
+}
+
+/// Verteilungen und Stellgrößen eines Szenarios.
+/// Wünsche treffen je Stunde nach einer Poisson-Verteilung ein (requests_per_day / 24, mal Wochentagsfaktor).
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScenarioConfig {
+    pub seed: u64,
+    pub days: u32,
+    pub base: ScenarioBase,
+    pub requests_per_day: f64,
+    // Faktor je Wochentag, Index 0 = current_day % 7
+    pub weekday_factors: [f64; 7],
+    // Anteil der Wünsche von Bestandskunden (sonst neue Person)
+    pub returning_share: f64,
+    // Größe des Kundenstamms; danach kommen alle Wünsche von Bestandskunden
+    pub max_persons: u32,
+    // Anteil der Wünsche nach Merkmalen (Klasse) statt nach einem bestimmten Auto
+    pub class_request_share: f64,
+    // Vorlauf bis zum Beginn des Zeitfensters und dessen Länge (Stunden)
+    pub lead_hours: ValueRange,
+    pub max_wait_hours: u32,
+    pub rental_hours: ValueRange,
+    pub trip_km: ValueRange,
+    // Tarif, den neue Personen buchen (None = ohne Tarif)
+    pub plan: Option<String>,
+    // Ersetzt die FleetPolicy des Ausgangszustands
+    pub policy: Option<FleetPolicy>,
+    // Zusätzliche Neuwagen, reihum auf die Stationen verteilt
+    pub extra_cars: u32,
+    pub extra_car_class: CarClass,
+}
+
+impl Default for ScenarioConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            days: 30,
+            base: ScenarioBase::Current,
+            requests_per_day: 10.0,
+            weekday_factors: [1.0; 7],
+            returning_share: 0.7,
+            max_persons: 500,
+            class_request_share: 0.5,
+            lead_hours: ValueRange { min: 0, max: 24 },
+            max_wait_hours: 2,
+            rental_hours: ValueRange { min: 1, max: 8 },
+            trip_km: ValueRange { min: 5, max: 120 },
+            plan: None,
+            policy: None,
+            extra_cars: 0,
+            extra_car_class: CarClass::Compact,
+        }
+    }
+}
+
+impl ScenarioConfig {
+    /// Prüft die Eingaben; die Meldung geht unverändert an den Aufrufer
+    pub fn validate(&self) -> Result<(), String> {
+        if self.days == 0 || self.days > MAX_SCENARIO_DAYS {
+            return Err(format!("days muss zwischen 1 und {} liegen", MAX_SCENARIO_DAYS));
+        }
+        if !(0.0..=MAX_REQUESTS_PER_DAY).contains(&self.requests_per_day) {
+            return Err(format!("requests_per_day muss zwischen 0 und {} liegen", MAX_REQUESTS_PER_DAY));
+        }
+        if self.weekday_factors.iter().any(|f| !(0.0..=10.0).contains(f)) {
+            return Err("weekday_factors müssen zwischen 0 und 10 liegen".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.returning_share) || !(0.0..=1.0).contains(&self.class_request_share) {
+            return Err("Anteile müssen zwischen 0 und 1 liegen".to_string());
+        }
+        for (name, range) in [("lead_hours", self.lead_hours), ("rental_hours", self.rental_hours), ("trip_km", self.trip_km)] {
+            if range.min > range.max {
+                return Err(format!("{}: min ist größer als max", name));
+            }
+        }
+        // 0 km gälte als No-Show, 0 Stunden als sofortige Rückgabe
+        if self.trip_km.min == 0 || self.rental_hours.min == 0 || self.max_wait_hours == 0 {
+            return Err("trip_km, rental_hours und max_wait_hours brauchen mindestens 1".to_string());
+        }
+        if self.max_persons == 0 || self.max_persons > MAX_PERSONS {
+            return Err(format!("max_persons muss zwischen 1 und {} liegen", MAX_PERSONS));
+        }
+        if self.extra_cars > MAX_EXTRA_CARS {
+            return Err(format!("extra_cars darf höchstens {} sein", MAX_EXTRA_CARS));
+        }
+        Ok(())
+    }
+}
+
+/// Kennzahlen eines simulierten Tages
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct ScenarioDay {
+    pub day: u32,
+    pub requests: u32,
+    // Gleich beim Reservieren abgelehnt (Obergrenzen, Tarif, kein passendes Auto)
+    // oder nicht gestellt, weil der ganze Kundenstamm schon fährt oder wartet
+    pub refused: u32,
+    pub fulfilled: u32,
+    // Abgelehnt, storniert oder ohne Auto verfallen
+    pub unmet: u32,
+    // Anteil der Autostunden in Ausleihe (ohne ausgemusterte Autos)
+    pub utilization: f64,
+}
+
+/// Ergebnis eines Szenarios; der gespeicherte Zustand bleibt unverändert
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct ScenarioReport {
+    pub config: ScenarioConfig,
+    pub start_day: u32,
+    pub end_day: u32,
+    pub fleet_size: u32,
+    pub persons_created: u32,
+    pub requests: u32,
+    pub refused: u32,
+    pub fulfilled: u32,
+    pub unmet_demand: u32,
+    // Am Ende noch wartende Reservierungen
+    pub still_open: u32,
+    pub rentals_completed: u32,
+    pub driven_km: u64,
+    pub utilization: f64,
+    pub cars_retired: u32,
+    pub revenue_cents: u64,
+    pub daily: Vec<ScenarioDay>,
+}
+
+// Laufende Ausleihe einer synthetischen Person
+struct Trip {
+    car_id: String,
+    due_at: u64,
+    km: u32,
+}
+
+/// Führt ein Szenario auf einer Kopie von base aus. Gleicher Seed und gleiche Eingaben ergeben denselben Bericht.
+pub fn run_scenario(base: &CarSharing, config: &ScenarioConfig) -> ScenarioReport {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut cs = match config.base {
+        ScenarioBase::Current => base.clone(),
+        ScenarioBase::Empty => CarSharing::new(),
+    };
+    if let Some(policy) = &config.policy {
+        cs.policy = policy.clone();
+    }
+    add_extra_cars(&mut cs, config);
+
+    let start_day = cs.current_day;
+    let start_charges = cs.charges.len();
+    let retired_before = status_counts(&cs.cars).retired;
+    let fleet_size = cs.cars.iter().filter(|c| c.status != CarStatus::Retired).count() as u32;
+
+    let mut persons: Vec<String> = Vec::new();
+    let mut synthetic: HashSet<String> = HashSet::new();
+    let mut trips: HashMap<String, Trip> = HashMap::new();
+    let mut daily: Vec<ScenarioDay> = (start_day..start_day + config.days)
+        .map(|day| ScenarioDay { day, requests: 0, refused: 0, fulfilled: 0, unmet: 0, utilization: 0.0 })
+        .collect();
+    // Autostunden je Tag: in Ausleihe und insgesamt
+    let mut hours: Vec<(u64, u64)> = vec![(0, 0); config.days as usize];
+    let mut rentals_completed = 0;
+    let mut driven_km: u64 = 0;
+
+    // Schritte von einer Stunde; beginnt der Ausgangszustand mitten am Tag, ist der erste Tag kürzer
+    while cs.current_day < start_day + config.days {
+        let index = (cs.current_day - start_day) as usize;
+        // Erster Schritt eines neuen Tages
+        if cs.minute_of_day < MINUTES_PER_HOUR as u32 {
+            let today = cs.current_day;
+            fold_settled(&mut cs, &synthetic, &mut daily, today);
+        }
+
+        // Fällige Rückgaben
+        let now = cs.now();
+        let mut due: Vec<String> = trips.iter().filter(|(_, t)| t.due_at <= now).map(|(p, _)| p.clone()).collect();
+        due.sort();
+        for person_id in due {
+            match trips.remove(&person_id) {
+                Some(trip) if cs.return_car(&person_id, &trip.car_id, trip.km, ReturnDetails::default()) => {
+                    rentals_completed += 1;
+                    driven_km += trip.km as u64;
+                }
+                _ => {}
+            }
+        }
+
+        // Neue Wünsche
+        let factor = config.weekday_factors[(cs.current_day % 7) as usize];
+        let arrivals = poisson(&mut rng, config.requests_per_day * factor / HOURS_PER_DAY as f64);
+        for _ in 0..arrivals {
+            daily[index].requests += 1;
+            let placed = match pick_person(&mut cs, &mut rng, config, &mut persons, &mut synthetic, &trips) {
+                Some(person_id) => request(&mut cs, &mut rng, config, &person_id),
+                None => false,
+            };
+            if !placed {
+                daily[index].refused += 1;
+            }
+        }
+
+        cs.advance_minutes(MINUTES_PER_HOUR);
+
+        // Abholungen: Ausleihen synthetischer Personen, die noch keine Fahrt haben
+        let picked_up: Vec<(String, String)> = cs
+            .rentals
+            .iter()
+            .filter(|(p, _)| synthetic.contains(p) && !trips.contains_key(p))
+            .cloned()
+            .collect();
+        for (person_id, car_id) in picked_up {
+            let started = cs.cars.iter().find(|c| c.identifier == car_id).and_then(|c| c.rented_at).unwrap_or(cs.now());
+            let due_at = started + config.rental_hours.sample(&mut rng) as u64 * MINUTES_PER_HOUR;
+            trips.insert(person_id, Trip { car_id, due_at, km: config.trip_km.sample(&mut rng) });
+        }
+
+        hours[index].0 += cs.cars.iter().filter(|c| c.status == CarStatus::Rented).count() as u64;
+        hours[index].1 += cs.cars.iter().filter(|c| c.status != CarStatus::Retired).count() as u64;
+    }
+    for (row, (rented, fleet)) in daily.iter_mut().zip(hours.iter()) {
+        row.utilization = ratio(*rented, *fleet);
+    }
+
+    fold_settled(&mut cs, &synthetic, &mut daily, u32::MAX);
+    let still_open = cs.reservation_history.iter().filter(|r| synthetic.contains(&r.person_id)).count() as u32;
+    let fulfilled: u32 = daily.iter().map(|d| d.fulfilled).sum();
+    let unmet: u32 = daily.iter().map(|d| d.unmet).sum();
+    let requests: u32 = daily.iter().map(|d| d.requests).sum();
+    let refused: u32 = daily.iter().map(|d| d.refused).sum();
+
+    ScenarioReport {
+        config: config.clone(),
+        start_day,
+        end_day: cs.current_day,
+        fleet_size,
+        persons_created: persons.len() as u32,
+        requests,
+        refused,
+        fulfilled,
+        unmet_demand: refused + unmet,
+        still_open,
+        rentals_completed,
+        driven_km,
+        utilization: ratio(hours.iter().map(|h| h.0).sum(), hours.iter().map(|h| h.1).sum()),
+        cars_retired: status_counts(&cs.cars).retired.saturating_sub(retired_before),
+        revenue_cents: cs.charges[start_charges..].iter().map(|c| c.amount_cents as u64).sum(),
+        daily,
+    }
+}
+
+// Abgeschlossene Reservierungen synthetischer Personen, die vor before_day eingegangen sind, in die Tageszeilen
+// übernehmen und aus der Kopie entfernen, ebenso deren Nachrichten und Ereignisse. Sonst wächst der Verlauf mit
+// jedem Wunsch, und jede Suche darin wird langsamer. Reservierungen von heute zählen noch für max_reservations_per_day.
+fn fold_settled(cs: &mut CarSharing, synthetic: &HashSet<String>, daily: &mut [ScenarioDay], before_day: u32) {
+    cs.reservation_history.retain(|record| {
+        if !synthetic.contains(&record.person_id) || record.created_day >= before_day {
+            return true;
+        }
+        let row = daily.iter_mut().find(|d| d.day == record.created_day);
+        match (record.state, row) {
+            (ReservationState::Pending | ReservationState::Suspended, _) => return true,
+            (ReservationState::Fulfilled | ReservationState::NoShow, Some(row)) => row.fulfilled += 1,
+            (_, Some(row)) => row.unmet += 1,
+            (_, None) => {}
+        }
+        false
+    });
+    cs.notifications.retain(|n| !synthetic.contains(&n.person_id));
+    cs.reservation_events.retain(|e| !synthetic.contains(&e.person_id));
+}
+
+fn ratio(part: u64, whole: u64) -> f64 {
+    if whole == 0 { 0.0 } else { part as f64 / whole as f64 }
+}
+
+// Knuth; bei den erlaubten Raten (höchstens ca. 85 je Stunde) ohne Unterlauf
+fn poisson(rng: &mut StdRng, lambda: f64) -> u32 {
+    let limit = (-lambda).exp();
+    let mut k = 0;
+    let mut p: f64 = rng.r#gen();
+    while p > limit {
+        k += 1;
+        p *= rng.r#gen::<f64>();
+    }
+    k
+}
+
+// Neuwagen reihum auf die Stationen verteilen; ist keine Station mehr frei, entfällt das Auto
+fn add_extra_cars(cs: &mut CarSharing, config: &ScenarioConfig) {
+    let stations: Vec<String> = cs.stations.iter().map(|s| s.identifier.clone()).collect();
+    let mut n = 0;
+    for i in 0..config.extra_cars as usize {
+        loop {
+            n += 1;
+            if !cs.cars.iter().any(|c| c.identifier == format!("szenario-auto-{}", n)) {
+                break;
+            }
+        }
+        let mut car = Car {
+            registered_day: cs.current_day,
+            class: config.extra_car_class,
+            ..Car::new(&format!("szenario-auto-{}", n))
+        };
+        if stations.is_empty() {
+            cs.register_car(car);
+            continue;
+        }
+        for offset in 0..stations.len() {
+            car.station_id = Some(stations[(i + offset) % stations.len()].clone());
+            if cs.register_car(car.clone()) {
+                break;
+            }
+        }
+    }
+}
+
+// Bestandskunde ohne laufende Fahrt oder offene Reservierung, sonst eine neue Person,
+// solange der Kundenstamm nicht voll ist
+fn pick_person(
+    cs: &mut CarSharing,
+    rng: &mut StdRng,
+    config: &ScenarioConfig,
+    persons: &mut Vec<String>,
+    synthetic: &mut HashSet<String>,
+    trips: &HashMap<String, Trip>,
+) -> Option<String> {
+    let full = persons.len() as u32 >= config.max_persons;
+    if full || rng.gen_bool(config.returning_share) {
+        let busy: HashSet<&String> = cs
+            .rentals
+            .iter()
+            .map(|r| &r.0)
+            .chain(cs.reservations.iter().map(|r| &r.person_id))
+            .chain(cs.class_reservations.iter().map(|r| &r.person_id))
+            .chain(trips.keys())
+            .collect();
+        let idle: Vec<&String> = persons.iter().filter(|p| !busy.contains(p)).collect();
+        if !idle.is_empty() {
+            return Some(idle[rng.gen_range(0..idle.len())].clone());
+        }
+        if full {
+            return None;
+        }
+    }
+
+    let mut n = persons.len() + 1;
+    while cs.persons.iter().any(|p| p.identifier == format!("szenario-{}", n)) {
+        n += 1;
+    }
+    let person_id = format!("szenario-{}", n);
+    cs.register_person(Person {
+        license_valid_days: (config.days + 1) * 10,
+        license_classes: vec![
+            LicenseClass::B,
+            LicenseClass::BE,
+            LicenseClass::C1,
+            LicenseClass::C1E,
+            LicenseClass::C,
+            LicenseClass::CE,
+            LicenseClass::D1,
+            LicenseClass::D,
+        ],
+        birth_day: Some((cs.current_day as i64 - SYNTHETIC_AGE_YEARS * DAYS_PER_YEAR) as i32),
+        ..Person::new(&person_id)
+    });
+    if let Some(plan) = &config.plan {
+        cs.subscribe_person(&person_id, plan);
+    }
+    persons.push(person_id.clone());
+    synthetic.insert(person_id.clone());
+    Some(person_id)
+}
+
+// Reservierung mit Zeitfenster für ein zufälliges Auto bzw. eine zufällige Klasse der Flotte
+fn request(cs: &mut CarSharing, rng: &mut StdRng, config: &ScenarioConfig, person_id: &str) -> bool {
+    let cars: Vec<(String, CarClass)> = cs
+        .cars
+        .iter()
+        .filter(|c| c.status != CarStatus::Retired)
+        .map(|c| (c.identifier.clone(), c.class))
+        .collect();
+    if cars.is_empty() {
+        return false;
+    }
+    let (car_id, class) = cars[rng.gen_range(0..cars.len())].clone();
+    let start_at = cs.now() + config.lead_hours.sample(rng) as u64 * MINUTES_PER_HOUR;
+    let end_at = start_at + config.max_wait_hours as u64 * MINUTES_PER_HOUR;
+    if rng.gen_bool(config.class_request_share) {
+        let filter = CarFilter { class: Some(class), ..CarFilter::default() };
+        cs.reserve_class_between(person_id, filter, start_at, end_at)
+    } else {
+        cs.reserve_car_between(person_id, &car_id, start_at, end_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(extra_cars: u32) -> ScenarioConfig {
+        ScenarioConfig {
+            seed: 7,
+            base: ScenarioBase::Empty,
+            requests_per_day: 20.0,
+            extra_cars,
+            ..ScenarioConfig::default()
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_report() {
+        let base = CarSharing::new();
+        let first = run_scenario(&base, &config(5));
+        let second = run_scenario(&base, &config(5));
+        assert!(first.requests > 0);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn more_cars_do_not_increase_unmet_demand() {
+        let base = CarSharing::new();
+        let small = run_scenario(&base, &config(5));
+        let large = run_scenario(&base, &config(15));
+        assert!(small.unmet_demand > 0);
+        assert!(large.unmet_demand <= small.unmet_demand);
+        assert!(large.fulfilled >= small.fulfilled);
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::carsharing::{
+    retirement_score, Car, CarSharing, CarStatus, MAINTENANCE_KM, TUV_KM,
+};
+
+// Ab wie vielen Rest-Kilometern ein Auto als "bald fällig" gilt
+const MAINTENANCE_WARN_KM: u32 = 500;
+const TUV_WARN_KM: u32 = 1000;
+// Breite der Buckets für die Verteilung des retirement_score
+const SCORE_BUCKET_WIDTH: f32 = 0.2;
+const SCORE_BUCKETS: usize = 5;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct StatusCounts {
+    pub available: u32,
+    pub rented: u32,
+    pub maintenance: u32,
+    pub tuv: u32,
+    #[serde(default)]
+    pub repair: u32,
+    #[serde(default)]
+    pub charging: u32,
+    #[serde(default)]
+    pub relocating: u32,
+    pub retired: u32,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScoreBucket {
+    pub from: f32,
+    // None für den letzten Bucket (score > 1.0, also reif für die Ausmusterung)
+    pub to: Option<f32>,
+    pub count: u32,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FleetStatistics {
+    pub current_day: u32,
+    pub total_cars: u32,
+    pub cars_per_status: StatusCounts,
+    // Anteil der vermieteten Autos an allen nicht ausgemusterten Autos (0.0 - 1.0)
+    pub utilization_rate: f32,
+    pub average_mileage: f32,
+    pub average_age_days: f32,
+    pub retirement_score_distribution: Vec<ScoreBucket>,
+    pub cars_near_maintenance: Vec<String>,
+    pub cars_near_tuv: Vec<String>,
+    pub blocked_persons: Vec<String>,
+    pub reservation_backlog: u32,
+    // Anteil der erfüllten an allen angelegten Reservierungen (0.0 - 1.0)
+    pub fulfillment_rate: f32,
+}
+
+/// Person, deren Führerschein bald abläuft oder die in der Kulanzfrist ist
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct LicenseExpiry {
+    pub person_id: String,
+    pub license_valid_days: u32,
+    pub grace_days_left: Option<u32>,
+    // Tage bis zur Sperre (Gültigkeit + Kulanzfrist)
+    pub days_until_block: u32,
+}
+
+/// Momentaufnahme am Ende eines simulierten Tages
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DailySnapshot {
+    pub day: u32,
+    pub cars: StatusCounts,
+    pub active_rentals: u32,
+    pub reservations_pending: u32,
+    // Alle folgenden Werte beziehen sich nur auf diesen Tag
+    pub reservations_fulfilled: u32,
+    pub licenses_expired: u32,
+    pub cars_retired: u32,
+}
+
+/// Zählt die Autos je Status
+pub fn status_counts(cars: &[Car]) -> StatusCounts {
+    let mut counts = StatusCounts::default();
+    for c in cars.iter() {
+        match c.status {
+            CarStatus::Available => counts.available += 1,
+            CarStatus::Rented => counts.rented += 1,
+            CarStatus::Maintenance(_) => counts.maintenance += 1,
+            CarStatus::Tuv(_) => counts.tuv += 1,
+            CarStatus::Repair(_) => counts.repair += 1,
+            CarStatus::Charging(_) => counts.charging += 1,
+            CarStatus::Relocating(_) => counts.relocating += 1,
+            CarStatus::Retired => counts.retired += 1,
+        }
+    }
+    counts
+}
+
+/// Berechnet die Kennzahlen der Flotte aus dem aktuellen Zustand
+pub fn fleet_statistics(cs: &CarSharing) -> FleetStatistics {
+    let counts = status_counts(&cs.cars);
+
+    let total_cars = cs.cars.len() as u32;
+    let in_service = total_cars - counts.retired;
+
+    let mut distribution: Vec<ScoreBucket> = (0..=SCORE_BUCKETS)
+        .map(|i| ScoreBucket {
+            from: i as f32 * SCORE_BUCKET_WIDTH,
+            to: if i == SCORE_BUCKETS { None } else { Some((i + 1) as f32 * SCORE_BUCKET_WIDTH) },
+            count: 0,
+        })
+        .collect();
+    for c in cs.cars.iter() {
+        let bucket = ((retirement_score(c) / SCORE_BUCKET_WIDTH) as usize).min(SCORE_BUCKETS);
+        distribution[bucket].count += 1;
+    }
+
+    let active_cars = cs.cars.iter().filter(|c| c.status != CarStatus::Retired);
+
+    FleetStatistics {
+        current_day: cs.current_day,
+        total_cars,
+        utilization_rate: ratio(counts.rented, in_service),
+        cars_per_status: counts,
+        average_mileage: average(cs.cars.iter().map(|c| c.mileage)),
+        average_age_days: average(cs.cars.iter().map(|c| c.age_days)),
+        retirement_score_distribution: distribution,
+        cars_near_maintenance: active_cars
+            .clone()
+            .filter(|c| km_until_next(c.mileage, MAINTENANCE_KM) <= MAINTENANCE_WARN_KM)
+            .map(|c| c.identifier.clone())
+            .collect(),
+        cars_near_tuv: active_cars
+            .filter(|c| km_until_next(c.mileage, TUV_KM) <= TUV_WARN_KM)
+            .map(|c| c.identifier.clone())
+            .collect(),
+        blocked_persons: cs
+            .persons
+            .iter()
+            .filter(|p| p.status.is_blocked())
+            .map(|p| p.identifier.clone())
+            .collect(),
+        reservation_backlog: (cs.reservations.len() + cs.class_reservations.len()) as u32,
+        fulfillment_rate: ratio(cs.reservations_fulfilled, cs.reservations_created),
+    }
+}
+
+/// Nicht gesperrte Personen, die in höchstens `within_days` Tagen gesperrt werden, die dringendsten zuerst
+pub fn expiring_licenses(cs: &CarSharing, within_days: u32) -> Vec<LicenseExpiry> {
+    let mut expiring: Vec<LicenseExpiry> = cs
+        .persons
+        .iter()
+        .filter(|p| !p.status.is_blocked())
+        .map(|p| LicenseExpiry {
+            person_id: p.identifier.clone(),
+            license_valid_days: p.license_valid_days,
+            grace_days_left: p.grace_days_left,
+            days_until_block: match p.grace_days_left {
+                Some(days) => days,
+                None => p.license_valid_days + cs.policy.license_grace_days,
+            },
+        })
+        .filter(|e| e.days_until_block <= within_days)
+        .collect();
+    expiring.sort_by(|a, b| a.days_until_block.cmp(&b.days_until_block).then_with(|| a.person_id.cmp(&b.person_id)));
+    expiring
+}
+
+/// Kilometer bis zur nächsten Intervallgrenze (z.B. nächste Wartung)
+pub fn km_until_next(mileage: u32, interval: u32) -> u32 {
+    interval - (mileage % interval)
+}
+
+fn ratio(part: u32, whole: u32) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+fn average(values: impl Iterator<Item = u32>) -> f32 {
+    let (sum, n) = values.fold((0u64, 0u32), |(sum, n), v| (sum + v as u64, n + 1));
+    if n == 0 {
+        0.0
+    } else {
+        sum as f32 / n as f32
+    }
+}
@@ -0,0 +1,556 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::carsharing::{car_status_name, CarSharing};
+use crate::notifications::unix_now;
+
+/// Ereignisse, die Webhooks abonnieren können ("*" = alle)
+pub const EVENT_TYPES: [&str; 4] = ["rental.started", "rental.ended", "charge.created", "damage.reported"];
+
+// Abstand zwischen zwei Durchläufen des Versands
+const DISPATCH_INTERVAL_SECS: u64 = 5;
+const DISPATCH_BATCH: i64 = 50;
+// Nach so vielen Fehlversuchen wird die Zustellung aufgegeben
+pub const MAX_ATTEMPTS: u32 = 8;
+// Wartezeit vor dem n-ten erneuten Versuch: RETRY_BASE_SECS * 2^(n-1)
+const RETRY_BASE_SECS: i64 = 30;
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+/// Registrierter Empfänger; das Geheimnis wird nur beim Anlegen ausgegeben
+#[derive(Clone, Debug, Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub created_at: i64,
+}
+
+/// Zustellung eines Ereignisses an einen Webhook (Zustellprotokoll)
+#[derive(Clone, Debug, Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event_id: i64,
+    pub event_type: String,
+    pub payload: Value,
+    // pending, delivered oder failed
+    pub status: String,
+    pub attempts: u32,
+    pub last_status_code: Option<u16>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+/// Legt die Tabellen für Webhooks, Ereignisse und Zustellungen an
+pub async fn create_tables(pool: &SqlitePool) {
+    sqlx::query(
+        r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                events TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS webhook_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                type TEXT NOT NULL,
+                day INTEGER NOT NULL,
+                data TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                webhook_id INTEGER NOT NULL,
+                event_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_status_code INTEGER,
+                last_error TEXT,
+                next_attempt_at INTEGER NOT NULL,
+                delivered_at INTEGER
+            );
+            "#,
+    )
+    .execute(pool)
+    .await
+    .expect("Webhook-Tabellen konnten nicht erstellt werden");
+}
+
+/// Ereignisse zwischen zwei Ständen: neue und beendete Ausleihen, neue Gebühren und Schäden.
+/// Eine Ausleihe erkennt man an Person, Auto und Ausleihzähler des Autos; so erzeugt auch
+/// eine zwischen zwei Speicherungen beendete und neu begonnene Ausleihe beide Ereignisse.
+pub fn domain_events(old: &CarSharing, new: &CarSharing) -> Vec<(&'static str, Value)> {
+    let mut events: Vec<(&'static str, Value)> = Vec::new();
+    let car = |cs: &CarSharing, car_id: &str| cs.cars.iter().find(|c| c.identifier == car_id).cloned();
+    let rental_key = |cs: &CarSharing, (person_id, car_id): &(String, String)| {
+        (person_id.clone(), car_id.clone(), car(cs, car_id).map(|c| c.rental_count))
+    };
+    let old_rentals: HashSet<_> = old.rentals.iter().map(|r| rental_key(old, r)).collect();
+    let new_rentals: HashSet<_> = new.rentals.iter().map(|r| rental_key(new, r)).collect();
+
+    for (person_id, car_id) in old.rentals.iter().filter(|r| !new_rentals.contains(&rental_key(old, r))) {
+        let returned = car(new, car_id);
+        events.push((
+            "rental.ended",
+            json!({
+                "person_id": person_id,
+                "car_id": car_id,
+                "station_id": returned.as_ref().and_then(|c| c.station_id.clone()),
+                "mileage": returned.as_ref().map(|c| c.mileage),
+                "energy_level": returned.as_ref().map(|c| c.energy_level),
+                "status": returned.as_ref().map(|c| car_status_name(&c.status)),
+            }),
+        ));
+    }
+    for (person_id, car_id) in new.rentals.iter().filter(|r| !old_rentals.contains(&rental_key(new, r))) {
+        let station_id = car(new, car_id).and_then(|c| c.station_id);
+        events.push(("rental.started", json!({ "person_id": person_id, "car_id": car_id, "station_id": station_id })));
+    }
+    let old_charges: HashSet<u32> = old.charges.iter().map(|c| c.id).collect();
+    for charge in new.charges.iter().filter(|c| !old_charges.contains(&c.id)) {
+        events.push(("charge.created", json!(charge)));
+    }
+    for damage in new.damages.iter().filter(|d| !old.damages.iter().any(|o| o.id == d.id)) {
+        events.push(("damage.reported", json!(damage)));
+    }
+    events
+}
+
+/// Speichert die Ereignisse und legt für jeden passenden Webhook eine Zustellung an
+pub async fn publish(pool: &SqlitePool, day: u32, events: Vec<(&'static str, Value)>) {
+    if events.is_empty() {
+        return;
+    }
+    let hooks = load_webhooks(pool).await;
+    let mut tx = pool.begin().await.unwrap();
+    let now = unix_now();
+
+    for (event_type, data) in events {
+        let event_id = sqlx::query("INSERT INTO webhook_events (type, day, data, created_at) VALUES (?, ?, ?, ?)")
+            .bind(event_type)
+            .bind(day)
+            .bind(data.to_string())
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+        let payload = json!({ "id": event_id, "type": event_type, "day": day, "created_at": now, "data": data }).to_string();
+
+        for hook in hooks.iter().filter(|h| h.events.iter().any(|e| e == "*" || e == event_type)) {
+            sqlx::query(
+                "INSERT INTO webhook_deliveries (webhook_id, event_id, event_type, payload, next_attempt_at) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(hook.id)
+            .bind(event_id)
+            .bind(event_type)
+            .bind(&payload)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+    }
+
+    tx.commit().await.unwrap();
+}
+
+/// Legt einen Webhook an. Ohne Geheimnis wird ein zufälliges erzeugt.
+pub async fn create_webhook(pool: &SqlitePool, url: &str, events: &[String], secret: Option<String>) -> Webhook {
+    let secret = secret.unwrap_or_else(|| hex::encode(rand::thread_rng().r#gen::<[u8; 32]>()));
+    let created_at = unix_now();
+    let id = sqlx::query("INSERT INTO webhooks (url, events, secret, created_at) VALUES (?, ?, ?, ?)")
+        .bind(url)
+        .bind(events.join(","))
+        .bind(&secret)
+        .bind(created_at)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+    Webhook { id, url: url.to_string(), events: events.to_vec(), secret, created_at }
+}
+
+/// Löscht den Webhook samt Zustellprotokoll
+pub async fn delete_webhook(pool: &SqlitePool, id: i64) -> bool {
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    let deleted = sqlx::query("DELETE FROM webhooks WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .unwrap()
+        .rows_affected();
+    tx.commit().await.unwrap();
+    deleted == 1
+}
+
+pub async fn load_webhooks(pool: &SqlitePool) -> Vec<Webhook> {
+    let rows = sqlx::query("SELECT * FROM webhooks ORDER BY id").fetch_all(pool).await.unwrap();
+    rows.iter().map(webhook_from_row).collect()
+}
+
+pub async fn load_webhook(pool: &SqlitePool, id: i64) -> Option<Webhook> {
+    sqlx::query("SELECT * FROM webhooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+        .map(|row| webhook_from_row(&row))
+}
+
+/// Zustellungen eines Webhooks, optional nach Status gefiltert, nach ID sortiert
+pub async fn load_deliveries(pool: &SqlitePool, webhook_id: i64, status: Option<&str>) -> Vec<WebhookDelivery> {
+    let rows = sqlx::query("SELECT * FROM webhook_deliveries WHERE webhook_id = ?1 AND (?2 IS NULL OR status = ?2) ORDER BY id")
+        .bind(webhook_id)
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    rows.iter().map(delivery_from_row).collect()
+}
+
+pub async fn load_delivery(pool: &SqlitePool, id: i64) -> Option<WebhookDelivery> {
+    sqlx::query("SELECT * FROM webhook_deliveries WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+        .map(|row| delivery_from_row(&row))
+}
+
+/// Setzt eine aufgegebene Zustellung zurück, damit sie erneut versucht wird
+pub async fn retry_delivery(pool: &SqlitePool, id: i64) -> bool {
+    let result = sqlx::query("UPDATE webhook_deliveries SET status = 'pending', attempts = 0, next_attempt_at = ? WHERE id = ? AND status = 'failed'")
+        .bind(unix_now())
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    result.rows_affected() == 1
+}
+
+/// Startet den Versand im Hintergrund
+pub fn spawn_dispatcher(pool: SqlitePool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(DISPATCH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = dispatch_due(&pool).await {
+                eprintln!("Versand der Webhooks fehlgeschlagen: {}", e);
+            }
+        }
+    });
+}
+
+/// Ein Durchlauf: stellt alle fälligen Ereignisse zu.
+/// Nur Antworten mit 2xx gelten als zugestellt; sonst wird mit wachsendem Abstand wiederholt,
+/// nach MAX_ATTEMPTS Versuchen wird die Zustellung als failed markiert.
+pub async fn dispatch_due(pool: &SqlitePool) -> Result<u32, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM webhook_deliveries WHERE status = 'pending' AND next_attempt_at <= ? ORDER BY id LIMIT ?")
+        .bind(unix_now())
+        .bind(DISPATCH_BATCH)
+        .fetch_all(pool)
+        .await?;
+
+    let mut delivered = 0;
+    for row in rows.iter() {
+        let delivery = delivery_from_row(row);
+        let payload: String = row.get("payload");
+        let result = match load_webhook(pool, delivery.webhook_id).await {
+            Some(hook) => post_event(&hook, &delivery, &payload).await,
+            None => Err((None, "Webhook existiert nicht mehr".to_string())),
+        };
+
+        let attempts = delivery.attempts + 1;
+        let now = unix_now();
+        match result {
+            Ok(code) => {
+                delivered += 1;
+                sqlx::query(
+                    "UPDATE webhook_deliveries SET status = 'delivered', attempts = ?, last_status_code = ?, last_error = NULL, delivered_at = ? WHERE id = ?",
+                )
+                .bind(attempts)
+                .bind(code)
+                .bind(now)
+                .bind(delivery.id)
+                .execute(pool)
+                .await?;
+            }
+            Err((code, error)) => {
+                let status = if attempts >= MAX_ATTEMPTS { "failed" } else { "pending" };
+                sqlx::query(
+                    "UPDATE webhook_deliveries SET status = ?, attempts = ?, last_status_code = ?, last_error = ?, next_attempt_at = ? WHERE id = ?",
+                )
+                .bind(status)
+                .bind(attempts)
+                .bind(code)
+                .bind(error)
+                .bind(now + (RETRY_BASE_SECS << (attempts - 1).min(16)))
+                .bind(delivery.id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// Zerlegt eine http://-URL in Host, Port und Pfad (HTTPS wird nicht unterstützt)
+pub fn parse_http_url(url: &str) -> Result<(String, u16, String), String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("URL '{}' muss mit http:// beginnen", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| format!("Ungültiger Port in '{}'", url))?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("URL '{}' enthält keinen Host", url));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// HMAC-SHA256 über "<timestamp>.<body>", hex-kodiert.
+/// Empfänger berechnen denselben Wert und können über den Zeitstempel alte Nachrichten verwerfen.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC akzeptiert Schlüssel jeder Länge");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// Sendet das Ereignis per HTTP POST; Ok mit dem Statuscode bei 2xx, sonst Statuscode (falls vorhanden) und Fehler
+async fn post_event(hook: &Webhook, delivery: &WebhookDelivery, payload: &str) -> Result<u16, (Option<u16>, String)> {
+    let (host, port, path) = parse_http_url(&hook.url).map_err(|e| (None, e))?;
+    let timestamp = unix_now();
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: carsharing-webhooks\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         X-Carsharing-Event: {}\r\nX-Carsharing-Delivery: {}\r\nX-Carsharing-Timestamp: {}\r\nX-Carsharing-Signature: sha256={}\r\n\
+         Connection: close\r\n\r\n{}",
+        path,
+        host,
+        port,
+        payload.len(),
+        delivery.event_type,
+        delivery.id,
+        timestamp,
+        sign(&hook.secret, timestamp, payload),
+        payload,
+    );
+
+    let exchange = async {
+        let mut stream = TcpStream::connect((host.as_str(), port))
+            .await
+            .map_err(|e| format!("Verbindung zu {}:{} fehlgeschlagen: {}", host, port, e))?;
+        stream.write_all(request.as_bytes()).await.map_err(|e| format!("Senden fehlgeschlagen: {}", e))?;
+        let mut status_line = String::new();
+        BufReader::new(stream)
+            .read_line(&mut status_line)
+            .await
+            .map_err(|e| format!("Keine Antwort: {}", e))?;
+        // "HTTP/1.1 200 OK"
+        status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| format!("Ungültige HTTP-Antwort '{}'", status_line.trim_end()))
+    };
+
+    match tokio::time::timeout(Duration::from_secs(HTTP_TIMEOUT_SECS), exchange).await {
+        Err(_) => Err((None, format!("Keine Antwort innerhalb von {} Sekunden", HTTP_TIMEOUT_SECS))),
+        Ok(Err(e)) => Err((None, e)),
+        Ok(Ok(code)) if (200..300).contains(&code) => Ok(code),
+        Ok(Ok(code)) => Err((Some(code), format!("Empfänger antwortet mit HTTP {}", code))),
+    }
+}
+
+fn webhook_from_row(row: &SqliteRow) -> Webhook {
+    let events: String = row.get("events");
+    Webhook {
+        id: row.get("id"),
+        url: row.get("url"),
+        events: events.split(',').map(str::to_string).collect(),
+        secret: row.get("secret"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn delivery_from_row(row: &SqliteRow) -> WebhookDelivery {
+    let payload: String = row.get("payload");
+    WebhookDelivery {
+        id: row.get("id"),
+        webhook_id: row.get("webhook_id"),
+        event_id: row.get("event_id"),
+        event_type: row.get("event_type"),
+        payload: serde_json::from_str(&payload).unwrap_or(Value::Null),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        last_status_code: row.get("last_status_code"),
+        last_error: row.get("last_error"),
+        next_attempt_at: row.get("next_attempt_at"),
+        delivered_at: row.get("delivered_at"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carsharing::{Car, CarStatus, Charge, ChargeKind};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // Empfangene Anfrage: Kopfzeilen (Name in Kleinbuchstaben) und Body
+    struct Received {
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl Received {
+        fn header(&self, name: &str) -> &str {
+            self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str()).unwrap_or_default()
+        }
+    }
+
+    // Minimaler HTTP-Empfänger: beantwortet je Verbindung eine Anfrage mit dem nächsten Statuscode
+    async fn http_stub(listener: TcpListener, codes: Vec<u16>) -> Vec<Received> {
+        let mut received = Vec::new();
+        for code in codes {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(": ") {
+                    headers.push((name.to_lowercase(), value.to_string()));
+                }
+            }
+            let length: usize = headers.iter().find(|(n, _)| n == "content-length").map_or(0, |(_, v)| v.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+            reader
+                .into_inner()
+                .write_all(format!("HTTP/1.1 {} Stub\r\nContent-Length: 0\r\n\r\n", code).as_bytes())
+                .await
+                .unwrap();
+            received.push(Received { headers, body: String::from_utf8(body).unwrap() });
+        }
+        received
+    }
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        create_tables(&pool).await;
+        pool
+    }
+
+    #[tokio::test]
+    async fn dispatch_signs_and_retries_with_backoff() {
+        let pool = test_pool().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://127.0.0.1:{}/hooks", listener.local_addr().unwrap().port());
+        let server = tokio::spawn(http_stub(listener, vec![500, 204]));
+        let hook = create_webhook(&pool, &url, &["charge.created".to_string()], Some("geheim".to_string())).await;
+        publish(&pool, 4, vec![("charge.created", json!({ "id": 1 })), ("damage.reported", json!({ "id": 2 }))]).await;
+
+        // Erster Versuch: Empfänger antwortet 500, die Zustellung wird später wiederholt
+        let before = unix_now();
+        assert_eq!(dispatch_due(&pool).await.unwrap(), 0);
+        let deliveries = load_deliveries(&pool, hook.id, None).await;
+        assert_eq!(deliveries.len(), 1);
+        let first = &deliveries[0];
+        assert_eq!(first.status, "pending");
+        assert_eq!(first.attempts, 1);
+        assert_eq!(first.last_status_code, Some(500));
+        assert!(first.last_error.is_some());
+        assert!(first.next_attempt_at >= before + RETRY_BASE_SECS);
+        assert!(first.next_attempt_at <= unix_now() + RETRY_BASE_SECS);
+
+        // Noch nicht fällig: kein weiterer Versuch
+        assert_eq!(dispatch_due(&pool).await.unwrap(), 0);
+        assert_eq!(load_delivery(&pool, first.id).await.unwrap().attempts, 1);
+
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = 0").execute(&pool).await.unwrap();
+        assert_eq!(dispatch_due(&pool).await.unwrap(), 1);
+        let second = load_delivery(&pool, first.id).await.unwrap();
+        assert_eq!(second.status, "delivered");
+        assert_eq!(second.attempts, 2);
+        assert_eq!(second.last_status_code, Some(204));
+        assert_eq!(second.last_error, None);
+        assert!(second.delivered_at.is_some());
+
+        let received = server.await.unwrap();
+        for request in received.iter() {
+            let timestamp: i64 = request.header("x-carsharing-timestamp").parse().unwrap();
+            assert_eq!(request.header("x-carsharing-signature"), format!("sha256={}", sign("geheim", timestamp, &request.body)));
+            assert_eq!(request.header("x-carsharing-event"), "charge.created");
+            assert_eq!(request.header("x-carsharing-delivery"), first.id.to_string());
+            let body: Value = serde_json::from_str(&request.body).unwrap();
+            assert_eq!(body["type"], "charge.created");
+            assert_eq!(body["day"], 4);
+            assert_eq!(body["data"], json!({ "id": 1 }));
+        }
+        assert_eq!(received.len(), 2);
+    }
+
+    #[test]
+    fn domain_events_detect_identical_charges_and_restarted_rentals() {
+        let mut old = CarSharing::new();
+        old.cars.push(Car {
+            status: CarStatus::Rented,
+            rental_count: 1,
+            rented_day: Some(0),
+            rented_at: Some(0),
+            ..Car::new("c1")
+        });
+        old.rentals.push(("p1".to_string(), "c1".to_string()));
+
+        let mut new = old.clone();
+        // Ausleihe beendet und neu begonnen, dazu zwei gleich aussehende Gebühren
+        new.cars[0].rental_count = 2;
+        for id in [1, 2] {
+            new.charges.push(Charge {
+                id,
+                person_id: "p1".to_string(),
+                car_id: "c1".to_string(),
+                day: 0,
+                kind: ChargeKind::RentalTime,
+                amount_cents: 250,
+            });
+        }
+
+        let events: Vec<&str> = domain_events(&old, &new).into_iter().map(|(event_type, _)| event_type).collect();
+        assert_eq!(events, vec!["rental.ended", "rental.started", "charge.created", "charge.created"]);
+        assert!(domain_events(&new, &new).is_empty());
+    }
+}
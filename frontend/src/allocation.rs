@@ -0,0 +1,324 @@
+use serde::{Deserialize, Serialize};
+
+use crate::carsharing::{may_drive, plan_allows, AllocationStrategy, CarSharing, CarSharingService};
+
+pub const ALL_STRATEGIES: [AllocationStrategy; 4] = [
+    AllocationStrategy::StrictPriority,
+    AllocationStrategy::Fifo,
+    AllocationStrategy::PriorityWithAging,
+    AllocationStrategy::MaximumMatching,
+];
+
+/// Ergebnis einer Zuteilung, ohne den Zustand zu verändern
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AllocationOutcome {
+    pub strategy: AllocationStrategy,
+    // (person_id, car_id)
+    pub assignments: Vec<(String, String)>,
+    pub fulfilled: u32,
+}
+
+// Offene Reservierung mit den Autos, die sie heute bedienen könnten (beste zuerst)
+struct Request {
+    person_id: String,
+    priority: u32,
+    created_day: u32,
+    // Position in der Warteschlange: erst die gezielten, dann die nach Merkmalen
+    order: usize,
+    candidates: Vec<String>,
+}
+
+/// Welche Person heute welches Auto bekommt.
+/// Jede Person bekommt höchstens ein Auto, jedes Auto geht an höchstens eine Person.
+pub fn allocate(cs: &CarSharing, strategy: AllocationStrategy) -> Vec<(String, String)> {
+    let mut requests = open_requests(cs);
+    match strategy {
+        AllocationStrategy::StrictPriority => {
+            requests.sort_by_key(|r| (std::cmp::Reverse(r.priority), r.order));
+            allocate_in_order(&requests)
+        }
+        AllocationStrategy::Fifo => {
+            requests.sort_by_key(|r| (r.created_day, r.order));
+            allocate_in_order(&requests)
+        }
+        AllocationStrategy::PriorityWithAging => {
+            let per_level = cs.policy.aging_days_per_priority.max(1);
+            requests.sort_by_key(|r| {
+                let waited = cs.current_day.saturating_sub(r.created_day);
+                (std::cmp::Reverse(r.priority.saturating_add(waited / per_level)), r.created_day, r.order)
+            });
+            allocate_in_order(&requests)
+        }
+        AllocationStrategy::MaximumMatching => {
+            requests.sort_by_key(|r| (std::cmp::Reverse(r.priority), r.order));
+            maximum_matching(&requests)
+        }
+    }
+}
+
+/// Alle Strategien auf dem aktuellen Zustand im Vergleich
+pub fn compare_strategies(cs: &CarSharing) -> Vec<AllocationOutcome> {
+    ALL_STRATEGIES
+        .iter()
+        .map(|&strategy| {
+            let assignments = allocate(cs, strategy);
+            AllocationOutcome {
+                strategy,
+                fulfilled: assignments.len() as u32,
+                assignments,
+            }
+        })
+        .collect()
+}
+
+fn open_requests(cs: &CarSharing) -> Vec<Request> {
+    let available = cs.get_available_cars();
+    let can_rent = |person_id: &str| {
+        cs.persons.iter().all(|p| p.identifier != person_id || !p.status.is_blocked())
+            && !cs.rentals.iter().any(|r| r.0 == person_id)
+    };
+    // Führerscheinklasse, Mindestalter und Tarif (unbekannte Personen scheitern später an rent_car)
+    let allowed = |person_id: &str, car_id: &str| {
+        let person = cs.persons.iter().find(|p| p.identifier == person_id);
+        let car = cs.cars.iter().find(|c| c.identifier == car_id);
+        match (person, car) {
+            (Some(p), Some(c)) => may_drive(p, c, &cs.policy, cs.current_day) && plan_allows(&cs.plans, p, c),
+            _ => true,
+        }
+    };
+
+    // Zeitfenster, die noch nicht begonnen haben, warten
+    let started = |start_at: Option<u64>| start_at.is_none_or(|start| start <= cs.now());
+
+    let mut requests: Vec<Request> = Vec::new();
+    for r in cs.reservations.iter().filter(|r| can_rent(&r.person_id) && started(r.start_at)) {
+        requests.push(Request {
+            person_id: r.person_id.clone(),
+            priority: r.priority,
+            created_day: r.created_day,
+            order: requests.len(),
+            candidates: available.iter().filter(|id| **id == r.car_id && allowed(&r.person_id, id)).cloned().collect(),
+        });
+    }
+    for r in cs.class_reservations.iter().filter(|r| can_rent(&r.person_id) && started(r.start_at)) {
+        // Autos, die eine andere Person gezielt reserviert hat, nur als letzte Wahl;
+        // danach möglichst wenig Übererfüllung, dann der höchste Füllstand
+        let mut cars: Vec<_> = cs
+            .cars
+            .iter()
+            .filter(|c| available.contains(&c.identifier) && r.filter.matches(c) && allowed(&r.person_id, &c.identifier))
+            .collect();
+        cars.sort_by_key(|c| {
+            let reserved_by_other = cs
+                .reservations
+                .iter()
+                .any(|x| x.car_id == c.identifier && x.person_id != r.person_id);
+            (reserved_by_other, r.filter.surplus(c), std::cmp::Reverse(c.energy_level), c.identifier.clone())
+        });
+        requests.push(Request {
+            person_id: r.person_id.clone(),
+            priority: r.priority,
+            created_day: r.created_day,
+            order: requests.len(),
+            candidates: cars.iter().map(|c| c.identifier.clone()).collect(),
+        });
+    }
+    requests
+}
+
+// Der Reihe nach: jede Reservierung nimmt das beste noch freie Auto
+fn allocate_in_order(requests: &[Request]) -> Vec<(String, String)> {
+    let mut assigned: Vec<(String, String)> = Vec::new();
+    for r in requests.iter() {
+        if assigned.iter().any(|(p, _)| p == &r.person_id) {
+            continue;
+        }
+        if let Some(car_id) = r.candidates.iter().find(|c| !assigned.iter().any(|(_, a)| a == *c)) {
+            assigned.push((r.person_id.clone(), car_id.clone()));
+        }
+    }
+    assigned
+}
+
+// Maximales bipartites Matching Personen <-> Autos (Augmenting Paths).
+// Personen werden in Prioritätsreihenfolge eingefügt; einmal versorgte Personen bleiben versorgt,
+// dadurch gewinnen bei gleicher Anzahl die höheren Prioritäten.
+fn maximum_matching(requests: &[Request]) -> Vec<(String, String)> {
+    let mut persons: Vec<&str> = Vec::new();
+    let mut cars: Vec<&str> = Vec::new();
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+
+    for r in requests.iter() {
+        let person = match persons.iter().position(|p| *p == r.person_id) {
+            Some(index) => index,
+            None => {
+                persons.push(&r.person_id);
+                edges.push(Vec::new());
+                persons.len() - 1
+            }
+        };
+        for car_id in r.candidates.iter() {
+            let car = match cars.iter().position(|c| c == car_id) {
+                Some(index) => index,
+                None => {
+                    cars.push(car_id);
+                    cars.len() - 1
+                }
+            };
+            if !edges[person].contains(&car) {
+                edges[person].push(car);
+            }
+        }
+    }
+
+    let mut car_owner: Vec<Option<usize>> = vec![None; cars.len()];
+    for person in 0..persons.len() {
+        let mut visited = vec![false; cars.len()];
+        try_assign(person, &edges, &mut car_owner, &mut visited);
+    }
+
+    let mut assigned: Vec<(String, String)> = Vec::new();
+    for (person, person_id) in persons.iter().enumerate() {
+        if let Some(car) = car_owner.iter().position(|o| *o == Some(person)) {
+            assigned.push((person_id.to_string(), cars[car].to_string()));
+        }
+    }
+    assigned
+}
+
+fn try_assign(person: usize, edges: &[Vec<usize>], car_owner: &mut [Option<usize>], visited: &mut [bool]) -> bool {
+    for &car in edges[person].iter() {
+        if visited[car] {
+            continue;
+        }
+        visited[car] = true;
+        let owner = car_owner[car];
+        if owner.is_none_or(|other| try_assign(other, edges, car_owner, visited)) {
+            car_owner[car] = Some(person);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::carsharing::{Car, CarClass, CarFilter, ClassReservation, Person, Reservation};
+
+    fn person(id: &str) -> Person {
+        Person { license_valid_days: 1000, ..Person::new(id) }
+    }
+
+    fn car(id: &str, class: CarClass, seats: u8) -> Car {
+        Car { class, seats, ..Car::new(id) }
+    }
+
+    fn reservation(person_id: &str, car_id: &str, priority: u32, created_day: u32) -> Reservation {
+        Reservation {
+            person_id: person_id.to_string(),
+            car_id: car_id.to_string(),
+            priority,
+            created_day,
+            start_at: None,
+            end_at: None,
+        }
+    }
+
+    fn class_reservation(id: u32, person_id: &str, filter: CarFilter, priority: u32) -> ClassReservation {
+        ClassReservation {
+            id,
+            person_id: person_id.to_string(),
+            filter,
+            priority,
+            created_day: 0,
+            start_at: None,
+            end_at: None,
+        }
+    }
+
+    fn state(persons: &[&str], cars: Vec<Car>) -> CarSharing {
+        let mut cs = CarSharing::new();
+        cs.persons = persons.iter().map(|id| person(id)).collect();
+        cs.cars = cars;
+        cs
+    }
+
+    fn persons_served(assignments: &[(String, String)]) -> Vec<&str> {
+        assignments.iter().map(|(p, _)| p.as_str()).collect()
+    }
+
+    #[test]
+    fn maximum_matching_serves_at_least_as_many_as_strict_priority() {
+        // p1 ist wichtiger und nimmt bei strenger Priorität den Mini, den p2 als einziges Auto fahren kann
+        let mut cs = state(&["p1", "p2"], vec![car("mini", CarClass::Mini, 4), car("van", CarClass::Van, 7)]);
+        cs.class_reservations.push(class_reservation(1, "p1", CarFilter::default(), 5));
+        cs.class_reservations.push(class_reservation(
+            2,
+            "p2",
+            CarFilter { class: Some(CarClass::Mini), ..CarFilter::default() },
+            1,
+        ));
+
+        let strict = allocate(&cs, AllocationStrategy::StrictPriority);
+        let matching = allocate(&cs, AllocationStrategy::MaximumMatching);
+        assert_eq!(strict, vec![("p1".to_string(), "mini".to_string())]);
+        assert!(matching.len() > strict.len());
+        assert!(matching.contains(&("p1".to_string(), "van".to_string())));
+        assert!(matching.contains(&("p2".to_string(), "mini".to_string())));
+
+        // Ohne Konkurrenz liefern beide gleich viel
+        cs.class_reservations.remove(1);
+        assert_eq!(
+            allocate(&cs, AllocationStrategy::MaximumMatching).len(),
+            allocate(&cs, AllocationStrategy::StrictPriority).len()
+        );
+    }
+
+    #[test]
+    fn priority_with_aging_promotes_old_low_priority_reservation() {
+        let mut cs = state(&["new", "old"], vec![car("c1", CarClass::Compact, 5)]);
+        cs.current_day = 20;
+        cs.policy.aging_days_per_priority = 5;
+        cs.reservations.push(reservation("new", "c1", 3, 20));
+        cs.reservations.push(reservation("old", "c1", 0, 0));
+
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::StrictPriority)), vec!["new"]);
+        // 20 Wartetage / 5 = 4 Stufen, damit liegt "old" vor "new"
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::PriorityWithAging)), vec!["old"]);
+
+        // 15 Wartetage ergeben nur 3 Stufen; bei Gleichstand gewinnt die ältere Reservierung
+        cs.current_day = 15;
+        cs.reservations[0].created_day = 15;
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::PriorityWithAging)), vec!["old"]);
+        cs.reservations[1].created_day = 1;
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::PriorityWithAging)), vec!["new"]);
+    }
+
+    #[test]
+    fn priority_with_aging_saturates_at_maximum_priority() {
+        let mut cs = state(&["p1", "p2"], vec![car("c1", CarClass::Compact, 5)]);
+        cs.current_day = 100;
+        cs.policy.aging_days_per_priority = 1;
+        cs.reservations.push(reservation("p1", "c1", u32::MAX, 50));
+        cs.reservations.push(reservation("p2", "c1", u32::MAX - 10, 0));
+
+        // Beide erreichen die Höchststufe, dann entscheidet das Anlegedatum
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::PriorityWithAging)), vec!["p2"]);
+    }
+
+    #[test]
+    fn fifo_orders_by_created_day() {
+        let mut cs = state(
+            &["p1", "p2", "p3"],
+            vec![car("c1", CarClass::Compact, 5), car("c2", CarClass::Compact, 5)],
+        );
+        cs.current_day = 10;
+        cs.reservations.push(reservation("p1", "c1", 9, 5));
+        cs.reservations.push(reservation("p2", "c1", 0, 2));
+        cs.reservations.push(reservation("p3", "c2", 5, 8));
+
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::Fifo)), vec!["p2", "p3"]);
+        assert_eq!(persons_served(&allocate(&cs, AllocationStrategy::StrictPriority)), vec!["p1", "p3"]);
+    }
+}
@@ -1,20 +1,121 @@
 // Consts for cars (kilometer)
-const MAINTENANCE_KM: u32 = 5000;
-const TUV_KM: u32 = 15000;
+pub const MAINTENANCE_KM: u32 = 5000;
+pub const TUV_KM: u32 = 15000;
 // Consts for cars (days)
 const MAINTENANCE_DAYS: u32 = 2;
 const TUV_DAYS: u32 = 3;
+// Standard-Reparaturdauer je Schwere (days)
+const REPAIR_DAYS_MAJOR: u32 = 3;
+const REPAIR_DAYS_BREAKDOWN: u32 = 7;
+// Tanken/Laden nach einer Rückgabe mit zu wenig Energie (days)
+const REFUEL_DAYS: u32 = 1;
+const RECHARGE_DAYS: u32 = 1;
+// Umsetzen zwischen Stationen: so viele km schafft ein Fahrer pro Tag
+const RELOCATION_KM_PER_DAY: f64 = 300.0;
+const EARTH_RADIUS_KM: f64 = 6371.0;
+// Füllstand in Prozent
+pub const FULL_ENERGY_LEVEL: u8 = 100;
+pub const DEFAULT_SEATS: u8 = 5;
 // Consts for retirement
-const MAX_AGE_DAYS: u32 = 3650;
-const MAX_KM: u32 = 200000;
-const MAX_RENTALS: u32 = 500;
+pub const MAX_AGE_DAYS: u32 = 3650;
+pub const MAX_KM: u32 = 200000;
+pub const MAX_RENTALS: u32 = 500;
+// Für das Alter von Personen
+pub const DAYS_PER_YEAR: i64 = 365;
+pub const DEFAULT_MIN_AGE_YEARS: u32 = 18;
+// Zeitpunkte sind Minuten seit Beginn von Tag 0; der Tag ist daraus abgeleitet
+pub const MINUTES_PER_HOUR: u64 = 60;
+pub const MINUTES_PER_DAY: u64 = 24 * MINUTES_PER_HOUR;
+// Abrechnungszeitraum der Mitgliedschaften (days)
+pub const DAYS_PER_MONTH: u32 = 30;
+// Priorität der Reservierungen von Personen ohne Tarif
+pub const BASIC_PRIORITY: u32 = 0;
 
 use serde::{Serialize, Deserialize};
 
+use crate::allocation::allocate;
+use crate::statistics::{status_counts, DailySnapshot};
+
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "StoredPersonStatus")]
 pub enum PersonStatus {
     Active,
-    Blocked,
+    Blocked(BlockReason),
+}
+
+impl PersonStatus {
+    pub fn is_blocked(&self) -> bool {
+        matches!(self, PersonStatus::Blocked(_))
+    }
+}
+
+// Ältere Stände speichern "Blocked" ohne Grund; gesperrt wurde damals fast immer wegen des Führerscheins
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPersonStatus {
+    Plain(String),
+    Blocked {
+        #[serde(rename = "Blocked")]
+        reason: BlockReason,
+    },
+}
+
+// Andere Zeichenketten sind kein gültiger Status und werden beim Laden abgelehnt
+impl TryFrom<StoredPersonStatus> for PersonStatus {
+    type Error = String;
+
+    fn try_from(stored: StoredPersonStatus) -> Result<Self, Self::Error> {
+        match stored {
+            StoredPersonStatus::Plain(s) if s == "Active" => Ok(PersonStatus::Active),
+            StoredPersonStatus::Plain(s) if s == "Blocked" => Ok(PersonStatus::Blocked(BlockReason::LicenseExpired)),
+            StoredPersonStatus::Plain(s) => Err(format!("Unbekannter Personenstatus '{}'", s)),
+            StoredPersonStatus::Blocked { reason } => Ok(PersonStatus::Blocked(reason)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum BlockReason {
+    LicenseExpired,
+    UnpaidInvoice,
+    // Schaden ab FleetPolicy::block_on_damage
+    Damage,
+    ManualSuspension,
+}
+
+// Eine von mehreren gleichzeitigen Sperren einer Person
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct PersonBlock {
+    pub reason: BlockReason,
+    pub since_day: u32,
+    // Ab diesem Tag ist die Sperre aufgehoben (None = bis sie ausdrücklich aufgehoben wird)
+    pub until_day: Option<u32>,
+}
+
+// Führerscheinklassen (EU)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum LicenseClass {
+    #[default]
+    B,
+    BE,
+    C1,
+    C1E,
+    C,
+    CE,
+    D1,
+    D,
+}
+
+impl LicenseClass {
+    // Höhere Klassen schließen niedrigere ein, z.B. BE auch B und CE auch C
+    pub fn covers(self, required: LicenseClass) -> bool {
+        use LicenseClass::*;
+        self == required
+            || matches!(
+                (self, required),
+                (BE, B) | (C1E, C1) | (C1E, BE) | (C, C1) | (CE, C) | (CE, C1) | (CE, C1E) | (CE, BE) | (D, D1)
+            )
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
@@ -23,14 +124,207 @@ pub enum CarStatus {
     Rented,
     Maintenance(u32),
     Tuv(u32),
+    Repair(u32),
+    Charging(u32),
+    // Unterwegs zur Zielstation (station_id ist bereits das Ziel)
+    Relocating(u32),
     Retired,
 }
 
+/// Name des Status ohne die verbleibenden Tage (für Filter)
+pub fn car_status_name(status: &CarStatus) -> &'static str {
+    match status {
+        CarStatus::Available => "available",
+        CarStatus::Rented => "rented",
+        CarStatus::Maintenance(_) => "maintenance",
+        CarStatus::Tuv(_) => "tuv",
+        CarStatus::Repair(_) => "repair",
+        CarStatus::Charging(_) => "charging",
+        CarStatus::Relocating(_) => "relocating",
+        CarStatus::Retired => "retired",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CarClass {
+    Mini,
+    #[default]
+    Compact,
+    Midsize,
+    Suv,
+    Van,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Powertrain {
+    #[default]
+    Combustion,
+    Electric,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Transmission {
+    #[default]
+    Manual,
+    Automatic,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ServiceKind {
+    Maintenance,
+    Tuv,
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Person {
     pub identifier: String,          // CHANGED: &'a str -> String + pub (Frontend braucht Zugriff)
     pub license_valid_days: u32,     // CHANGED: pub (optional, aber praktisch fürs Frontend)
     pub status: PersonStatus,        // CHANGED: pub
+    // Resttage der Kulanzfrist nach Ablauf des Führerscheins (None = keine Frist läuft)
+    #[serde(default)]
+    pub grace_days_left: Option<u32>,
+    #[serde(default)]
+    pub first_name: String,
+    #[serde(default)]
+    pub last_name: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default = "default_license_classes")]
+    pub license_classes: Vec<LicenseClass>,
+    // Geburtstag als Simulationstag (negativ = vor Tag 0, None = unbekannt)
+    #[serde(default)]
+    pub birth_day: Option<i32>,
+    // Alle aktiven Sperren; status ist Blocked mit dem Grund der ersten, solange die Liste nicht leer ist
+    #[serde(default)]
+    pub blocks: Vec<PersonBlock>,
+    #[serde(default)]
+    pub subscription: Option<Subscription>,
+    // Erfüllte Reservierungen, deren Auto nicht genutzt wurde
+    #[serde(default)]
+    pub no_shows: u32,
+    // Bis zu diesem Tag (ausschließlich) sind nach einem No-Show keine neuen Reservierungen möglich
+    #[serde(default)]
+    pub reservation_cooldown_until: Option<u32>,
+}
+
+// Monatlich buchbarer Tarif
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct MembershipPlan {
+    pub name: String,
+    pub monthly_fee_cents: u32,
+    // Wird bei jeder Rückgabe für die gefahrenen km berechnet
+    pub per_km_cents: u32,
+    // Zeitpreis je angefangene Stunde (None = FleetPolicy::per_hour_cents)
+    #[serde(default)]
+    pub per_hour_cents: Option<u32>,
+    // Priorität aller Reservierungen der Mitglieder
+    pub priority: u32,
+    // Offene Reservierungen gleichzeitig (gezielt und nach Merkmalen zusammen)
+    pub max_reservations: u32,
+    // Leer = alle Fahrzeugklassen
+    #[serde(default)]
+    pub allowed_classes: Vec<CarClass>,
+}
+
+impl MembershipPlan {
+    pub fn allows(&self, class: CarClass) -> bool {
+        self.allowed_classes.is_empty() || self.allowed_classes.contains(&class)
+    }
+}
+
+// Gebuchter Tarif einer Person
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Subscription {
+    pub plan: String,
+    pub since_day: u32,
+    // An diesem Tag wird die nächste Monatsgebühr fällig
+    pub renewal_day: u32,
+}
+
+impl Person {
+    // Aktive Person ohne Kontaktdaten, Tarif und Sperren; weitere Felder per `..Person::new(id)`
+    pub fn new(identifier: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            license_valid_days: 0,
+            status: PersonStatus::Active,
+            grace_days_left: None,
+            first_name: String::new(),
+            last_name: String::new(),
+            email: None,
+            phone: None,
+            license_classes: default_license_classes(),
+            birth_day: None,
+            blocks: vec![],
+            subscription: None,
+            no_shows: 0,
+            reservation_cooldown_until: None,
+        }
+    }
+
+    // Alter in vollen Jahren am angegebenen Tag
+    pub fn age_years(&self, day: u32) -> Option<u32> {
+        self.birth_day.map(|born| ((day as i64 - born as i64).max(0) / DAYS_PER_YEAR) as u32)
+    }
+
+    pub fn has_block(&self, reason: BlockReason) -> bool {
+        if self.blocks.is_empty() {
+            return self.status == PersonStatus::Blocked(reason);
+        }
+        self.blocks.iter().any(|b| b.reason == reason)
+    }
+
+    // Gleicher Grund erneut: es gilt die längere Sperre
+    pub fn add_block(&mut self, reason: BlockReason, day: u32, until_day: Option<u32>) {
+        self.migrate_block(day);
+        match self.blocks.iter_mut().find(|b| b.reason == reason) {
+            Some(block) => {
+                block.until_day = match (block.until_day, until_day) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    _ => None,
+                }
+            }
+            None => self.blocks.push(PersonBlock { reason, since_day: day, until_day }),
+        }
+        self.sync_status();
+    }
+
+    // Liefert false, wenn keine Sperre mit diesem Grund bestand
+    pub fn remove_block(&mut self, reason: BlockReason, day: u32) -> bool {
+        self.migrate_block(day);
+        let before = self.blocks.len();
+        self.blocks.retain(|b| b.reason != reason);
+        self.sync_status();
+        self.blocks.len() != before
+    }
+
+    // Hebt abgelaufene Sperren auf; true, wenn die Person dadurch wieder aktiv ist
+    fn lift_expired_blocks(&mut self, day: u32) -> bool {
+        self.migrate_block(day);
+        let before = self.blocks.len();
+        self.blocks.retain(|b| b.until_day.is_none_or(|until| until > day));
+        self.sync_status();
+        self.blocks.len() != before && self.blocks.is_empty()
+    }
+
+    // Stände vor der Sperrliste kennen nur den Status
+    fn migrate_block(&mut self, day: u32) {
+        match self.status {
+            PersonStatus::Blocked(reason) if self.blocks.is_empty() => {
+                self.blocks.push(PersonBlock { reason, since_day: day, until_day: None })
+            }
+            _ => {}
+        }
+    }
+
+    fn sync_status(&mut self) {
+        self.status = match self.blocks.first() {
+            Some(block) => PersonStatus::Blocked(block.reason),
+            None => PersonStatus::Active,
+        };
+    }
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,6 +334,71 @@ pub struct Car {
     pub status: CarStatus,           // CHANGED: pub
     pub age_days: u32,               // CHANGED: pub
     pub rental_count: u32,           // CHANGED: pub
+    // Für die Wartungsprognose: Tag der Registrierung und seitdem gefahrene km
+    #[serde(default)]
+    pub registered_day: u32,
+    #[serde(default)]
+    pub driven_km: u32,
+    // Tag, an dem der aktuelle Werkstattaufenthalt begonnen hat
+    #[serde(default)]
+    pub service_started_day: Option<u32>,
+    #[serde(default)]
+    pub powertrain: Powertrain,
+    // Tank- bzw. Akkustand in Prozent
+    #[serde(default = "full_energy_level")]
+    pub energy_level: u8,
+    // Station, an der das Auto steht (bei Vermietung: wo es abgeholt wurde)
+    #[serde(default)]
+    pub station_id: Option<String>,
+    #[serde(default)]
+    pub class: CarClass,
+    #[serde(default = "default_seats")]
+    pub seats: u8,
+    #[serde(default)]
+    pub transmission: Transmission,
+    // Ausstattung, z.B. "navi", "kindersitz", "anhängerkupplung"
+    #[serde(default)]
+    pub features: Vec<String>,
+    // Tag, an dem die laufende Ausleihe begonnen hat
+    #[serde(default)]
+    pub rented_day: Option<u32>,
+    // Beginn der laufenden Ausleihe in Minuten (None bei Ständen ohne Uhrzeit: Beginn von rented_day)
+    #[serde(default)]
+    pub rented_at: Option<u64>,
+}
+
+impl Car {
+    // Verfügbarer Neuwagen ohne Station mit vollem Tank; weitere Felder per `..Car::new(id)`
+    pub fn new(identifier: &str) -> Self {
+        Self {
+            identifier: identifier.to_string(),
+            mileage: 0,
+            status: CarStatus::Available,
+            age_days: 0,
+            rental_count: 0,
+            registered_day: 0,
+            driven_km: 0,
+            service_started_day: None,
+            powertrain: Powertrain::default(),
+            energy_level: FULL_ENERGY_LEVEL,
+            station_id: None,
+            class: CarClass::default(),
+            seats: DEFAULT_SEATS,
+            transmission: Transmission::default(),
+            features: vec![],
+            rented_day: None,
+            rented_at: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Station {
+    pub identifier: String,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub capacity: u32,
 }
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -47,15 +406,441 @@ pub struct Reservation {
     pub person_id: String,           // CHANGED: &'a str -> String + pub
     pub car_id: String,              // CHANGED: &'a str -> String + pub
     pub priority: u32,               // CHANGED: pub
+    #[serde(default)]
+    pub created_day: u32,
+    // Gewünschtes Zeitfenster in Minuten (None = ab sofort bzw. ohne Ende)
+    #[serde(default)]
+    pub start_at: Option<u64>,
+    #[serde(default)]
+    pub end_at: Option<u64>,
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+// Gewünschte Merkmale; None bzw. leer = egal
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CarFilter {
+    pub class: Option<CarClass>,
+    pub min_seats: Option<u8>,
+    pub transmission: Option<Transmission>,
+    pub powertrain: Option<Powertrain>,
+    // Alle genannten Ausstattungsmerkmale müssen vorhanden sein
+    pub features: Vec<String>,
+    pub station_id: Option<String>,
+}
+
+impl CarFilter {
+    pub fn matches(&self, car: &Car) -> bool {
+        self.class.is_none_or(|class| car.class == class)
+            && self.min_seats.is_none_or(|seats| car.seats >= seats)
+            && self.transmission.is_none_or(|t| car.transmission == t)
+            && self.powertrain.is_none_or(|p| car.powertrain == p)
+            && self.features.iter().all(|f| car.features.contains(f))
+            && (self.station_id.is_none() || car.station_id == self.station_id)
+    }
+
+    // Wie weit das Auto über den Wunsch hinausgeht (zusätzliche Sitze und Extras).
+    // Kleiner ist besser, damit große Autos für größere Wünsche frei bleiben.
+    pub fn surplus(&self, car: &Car) -> u32 {
+        let seats = car.seats.saturating_sub(self.min_seats.unwrap_or(0)) as u32;
+        let features = car.features.iter().filter(|f| !self.features.contains(f)).count() as u32;
+        seats + features
+    }
+}
+
+// Reservierung für irgendein passendes Auto statt für ein bestimmtes
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ClassReservation {
+    pub id: u32,
+    pub person_id: String,
+    pub filter: CarFilter,
+    pub priority: u32,
+    #[serde(default)]
+    pub created_day: u32,
+    #[serde(default)]
+    pub start_at: Option<u64>,
+    #[serde(default)]
+    pub end_at: Option<u64>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ReservationState {
+    Pending,
+    Fulfilled,
+    // Vom Kunden storniert
+    Cancelled,
+    // Zu lange nicht erfüllt (FleetPolicy::reservation_expiry_days)
+    Expired,
+    // Person gesperrt/abgemeldet, Auto ausgemustert/abgemeldet oder kein passendes Auto mehr
+    Rejected,
+    // Die Person hat über eine andere Reservierung schon ein Auto bekommen
+    Superseded,
+    // Person gesperrt; wird nach Verlängerung des Führerscheins wieder Pending
+    Suspended,
+    // Erfüllt, aber das Auto wurde ohne gefahrene km zurückgegeben
+    NoShow,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ReservationEventKind {
+    Cancelled,
+    Reassigned,
+    Suspended,
+    Resumed,
+}
+
+// Ereignis aus den Folgeregeln (Ausmusterung, Sperre), z.B. für Benachrichtigungen
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ReservationEvent {
+    pub id: u32,
+    pub day: u32,
+    // ReservationRecord::id
+    pub record_id: u32,
+    pub person_id: String,
+    pub kind: ReservationEventKind,
+    pub reason: String,
+}
+
+// Was mit offenen Reservierungen passiert, deren Auto ausgemustert wird
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum RetiredCarRule {
+    #[default]
+    Cancel,
+    // Auf ein vergleichbares Auto umbuchen (sonst stornieren)
+    Reassign,
+}
+
+// Was mit offenen Reservierungen einer gesperrten Person passiert
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum BlockedPersonRule {
+    #[default]
+    Cancel,
+    // Ruhen lassen, bis der Führerschein verlängert wird
+    Suspend,
+}
+
+// Ergebnis der Prüfung einer offenen Reservierung in sweep_reservations
+enum SweepAction {
+    Cancel(String),
+    Expire(String),
+    Suspend(String),
+    Reassign(String),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ReservationTransition {
+    pub day: u32,
+    pub state: ReservationState,
+    pub reason: String,
+}
+
+// Verlauf einer Reservierung; bleibt erhalten, wenn sie die Warteschlange verlässt
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ReservationRecord {
+    pub id: u32,
+    pub person_id: String,
+    // Gezielt reserviertes Auto bzw. Nr. der Reservierung nach Merkmalen
+    pub car_id: Option<String>,
+    pub class_reservation_id: Option<u32>,
+    pub priority: u32,
+    pub created_day: u32,
+    // Zeitfenster der Reservierung in Minuten
+    #[serde(default)]
+    pub start_at: Option<u64>,
+    #[serde(default)]
+    pub end_at: Option<u64>,
+    pub state: ReservationState,
+    // Bei Erfüllung: das übergebene Auto
+    pub assigned_car_id: Option<String>,
+    // Bis zu diesem Tag muss das übergebene Auto abgeholt werden (None = abgeholt oder keine Frist)
+    #[serde(default)]
+    pub pickup_due_day: Option<u32>,
+    // Die Ausleihe aus dieser Reservierung läuft noch (nur sie kann ein No-Show werden)
+    #[serde(default)]
+    pub rental_open: bool,
+    pub transitions: Vec<ReservationTransition>,
+}
+
+impl ReservationRecord {
+    fn for_car(r: &Reservation) -> Self {
+        Self::pending(&r.person_id, Some(r.car_id.clone()), None, r.priority, r.created_day).between(r.start_at, r.end_at)
+    }
+
+    fn for_class(r: &ClassReservation) -> Self {
+        Self::pending(&r.person_id, None, Some(r.id), r.priority, r.created_day).between(r.start_at, r.end_at)
+    }
+
+    fn between(self, start_at: Option<u64>, end_at: Option<u64>) -> Self {
+        Self { start_at, end_at, ..self }
+    }
+
+    fn pending(person_id: &str, car_id: Option<String>, class_reservation_id: Option<u32>, priority: u32, created_day: u32) -> Self {
+        Self {
+            id: 0,
+            person_id: person_id.to_string(),
+            car_id,
+            class_reservation_id,
+            priority,
+            created_day,
+            start_at: None,
+            end_at: None,
+            state: ReservationState::Pending,
+            assigned_car_id: None,
+            pickup_due_day: None,
+            rental_open: false,
+            transitions: Vec::new(),
+        }
+    }
+
+    fn is_open_for(&self, other: &ReservationRecord) -> bool {
+        matches!(self.state, ReservationState::Pending | ReservationState::Suspended)
+            && self.person_id == other.person_id
+            && self.car_id == other.car_id
+            && self.class_reservation_id == other.class_reservation_id
+    }
+}
+
+// Geplanter Werkstatttermin, der in simulate_n_days gestartet wird
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ScheduledService {
+    pub car_id: String,
+    pub kind: ServiceKind,
+    pub day: u32,
+    pub duration_days: u32,
+}
+
+// Reihenfolge ist wichtig: Vergleiche mit >= nutzen die Schwere
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum DamageSeverity {
+    Minor,
+    Major,
+    Breakdown,
+}
+
+// Schadensmeldung bei der Rückgabe
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DamageReport {
+    pub severity: DamageSeverity,
+    pub description: String,
+    pub location: String,
+    // None = Standarddauer je Schwere, Some(0) = Auto bleibt verfügbar
+    pub repair_days: Option<u32>,
+}
+
+// Optionale Angaben bei der Rückgabe
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct ReturnDetails {
+    // Tank-/Akkustand in Prozent (None = unverändert)
+    pub energy_level: Option<u8>,
+    pub damage: Option<DamageReport>,
+    // Rückgabestation (None = zurück zur Abholstation)
+    pub station_id: Option<String>,
+}
+
+// Gemeldeter Schaden, der Person zugeordnet, die das Auto zuletzt gemietet hatte
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Damage {
+    pub id: u32,
+    pub car_id: String,
+    pub person_id: String,
+    pub reported_day: u32,
+    pub severity: DamageSeverity,
+    pub description: String,
+    pub location: String,
+    pub resolved_day: Option<u32>,
+}
+
+// Nachweis über eine durchgeführte Wartung bzw. einen TÜV
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ServiceRecord {
+    pub car_id: String,
+    pub kind: ServiceKind,
+    pub start_day: u32,
+    pub end_day: u32,
+    pub mileage: u32,
+    pub notes: String,
+    pub cost_cents: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ChargeKind {
+    Refuel,
+    Recharge,
+    // Monatsgebühr des Tarifs
+    MembershipFee,
+    // Kilometerpreis des Tarifs
+    Mileage,
+    // Zeitpreis je angefangene Stunde der Ausleihe
+    RentalTime,
+}
+
+// Gebühr, die einer Person in Rechnung gestellt wird
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Charge {
+    // Laufende Nummer; ältere Zustände ohne Nummer haben 0
+    #[serde(default)]
+    pub id: u32,
+    pub person_id: String,
+    // Leer bei Monatsgebühren
+    pub car_id: String,
+    pub day: u32,
+    pub kind: ChargeKind,
+    pub amount_cents: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum NotificationKind {
+    // Erinnerung an einer der Schwellen aus license_reminder_days
+    LicenseExpiring,
+    // Führerschein abgelaufen, Kulanzfrist beginnt
+    LicenseGracePeriod,
+    // Führerschein abgelaufen, Person gesperrt
+    LicenseExpired,
+    ReservationFulfilled,
+    ReservationExpired,
+    // Ausleihe hat rental_due_days erreicht
+    CarDueBack,
+    // Neue Gebühr (siehe charges)
+    Invoice,
+    // Reserviertes Auto nicht genutzt, ggf. mit Sperrfrist für neue Reservierungen
+    NoShow,
+}
+
+// Nachricht an eine Person
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: u32,
+    pub day: u32,
+    pub person_id: String,
+    pub kind: NotificationKind,
+    pub message: String,
+}
+
+// Reihenfolge, in der offene Reservierungen Autos zugeteilt bekommen (siehe allocation.rs)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum AllocationStrategy {
+    // Höchste Priorität zuerst, bei Gleichstand Reihenfolge der Warteschlange
+    #[default]
+    StrictPriority,
+    // Älteste Reservierung zuerst
+    Fifo,
+    // Priorität steigt um 1 je aging_days_per_priority Wartetage
+    PriorityWithAging,
+    // Möglichst viele erfüllte Reservierungen, bei Gleichstand nach Priorität
+    MaximumMatching,
+}
+
+// Einstellbare Regeln für den Betrieb der Flotte
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FleetPolicy {
+    // Maximale Anzahl Autos gleichzeitig in Wartung/TÜV (None = unbegrenzt)
+    pub workshop_capacity: Option<u32>,
+    // Person wird gesperrt, wenn sie einen Schaden ab dieser Schwere verursacht (None = nie)
+    pub block_on_damage: Option<DamageSeverity>,
+    // Autos unter diesem Füllstand (Prozent) sind nicht mietbar und werden nach der Rückgabe getankt/geladen
+    pub min_energy_level: u8,
+    // Aufschlag je fehlendem Prozent, wenn ein Auto unter min_energy_level zurückgegeben wird
+    pub refuel_fee_cents_per_percent: u32,
+    pub recharge_fee_cents_per_percent: u32,
+    pub allocation: AllocationStrategy,
+    pub aging_days_per_priority: u32,
+    // Offene Reservierungen verfallen nach so vielen Tagen (None = nie)
+    pub reservation_expiry_days: Option<u32>,
+    pub on_car_retired: RetiredCarRule,
+    pub on_person_blocked: BlockedPersonRule,
+    // Erinnerung, wenn der Führerschein noch so viele Tage gültig ist
+    pub license_reminder_days: Vec<u32>,
+    // Tage nach Ablauf, bis die Person gesperrt wird (0 = sofort)
+    pub license_grace_days: u32,
+    // Nach so vielen Tagen Ausleihe wird an die Rückgabe erinnert (None = nie)
+    pub rental_due_days: Option<u32>,
+    // Mindestalter zum Mieten; Personen ohne Geburtstag gelten als alt genug
+    pub min_age_years: u32,
+    // Nötige Führerscheinklasse je Fahrzeugklasse; nicht aufgeführte Klassen brauchen B
+    pub license_requirements: Vec<(CarClass, LicenseClass)>,
+    // Offene Reservierungen je Person, zusätzlich zur Grenze des Tarifs (None = unbegrenzt)
+    pub max_open_reservations: Option<u32>,
+    // Neue Reservierungen je Person und Tag (None = unbegrenzt)
+    pub max_reservations_per_day: Option<u32>,
+    // Tage ohne neue Reservierungen nach einem No-Show (0 = keine Sperrfrist)
+    pub no_show_cooldown_days: u32,
+    // Frist in Tagen, um ein über eine Reservierung übergebenes Auto abzuholen (None = keine Frist)
+    pub pickup_deadline_days: Option<u32>,
+    // Zeitpreis je angefangene Stunde, sofern der Tarif keinen eigenen hat
+    pub per_hour_cents: u32,
+}
+
+impl Default for FleetPolicy {
+    fn default() -> Self {
+        Self {
+            workshop_capacity: None,
+            block_on_damage: None,
+            min_energy_level: 25,
+            refuel_fee_cents_per_percent: 40,
+            recharge_fee_cents_per_percent: 20,
+            allocation: AllocationStrategy::StrictPriority,
+            aging_days_per_priority: 7,
+            reservation_expiry_days: None,
+            on_car_retired: RetiredCarRule::Cancel,
+            on_person_blocked: BlockedPersonRule::Cancel,
+            license_reminder_days: vec![30, 7, 1],
+            license_grace_days: 0,
+            rental_due_days: None,
+            min_age_years: DEFAULT_MIN_AGE_YEARS,
+            license_requirements: Vec::new(),
+            max_open_reservations: None,
+            max_reservations_per_day: None,
+            no_show_cooldown_days: 0,
+            pickup_deadline_days: None,
+            per_hour_cents: 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct CarSharing {
     pub persons: Vec<Person>,                 // CHANGED: Vec<Person<'a>> -> Vec<Person>
     pub cars: Vec<Car>,                       // CHANGED: Vec<Car<'a>> -> Vec<Car>
     pub rentals: Vec<(String, String)>,       // CHANGED: Vec<(&'a str,&'a str)> -> Vec<(String,String)>
     pub reservations: Vec<Reservation>,       // CHANGED: Vec<Reservation<'a>> -> Vec<Reservation>
     pub current_day: u32,                     // CHANGED: pub (optional)
+    // Uhrzeit innerhalb von current_day in Minuten (0..MINUTES_PER_DAY)
+    #[serde(default)]
+    pub minute_of_day: u32,
+    // Zähler für die Erfüllungsquote (Statistik)
+    #[serde(default)]
+    pub reservations_created: u32,
+    #[serde(default)]
+    pub reservations_fulfilled: u32,
+    // Eine Momentaufnahme pro simuliertem Tag
+    #[serde(default)]
+    pub history: Vec<DailySnapshot>,
+    #[serde(default)]
+    pub scheduled_services: Vec<ScheduledService>,
+    #[serde(default)]
+    pub policy: FleetPolicy,
+    // Bleibt auch nach unregister_car erhalten (Nachweis)
+    #[serde(default)]
+    pub service_records: Vec<ServiceRecord>,
+    #[serde(default)]
+    pub damages: Vec<Damage>,
+    #[serde(default)]
+    pub charges: Vec<Charge>,
+    #[serde(default)]
+    pub stations: Vec<Station>,
+    #[serde(default)]
+    pub class_reservations: Vec<ClassReservation>,
+    #[serde(default)]
+    pub reservation_history: Vec<ReservationRecord>,
+    #[serde(default)]
+    pub reservation_events: Vec<ReservationEvent>,
+    #[serde(default)]
+    pub notifications: Vec<Notification>,
+    #[serde(default)]
+    pub plans: Vec<MembershipPlan>,
+    // Wird bei jedem Speichern im Backend hochgezählt; veraltete Stände erkennt man daran
+    #[serde(default)]
+    pub version: u64,
 }
 
 pub trait CarSharingService {
@@ -63,30 +848,69 @@ pub trait CarSharingService {
     fn register_person(&mut self, p: Person) -> bool;                 // CHANGED: Person<'a> -> Person
     fn unregister_person(&mut self, identifier: &str) -> bool;
     fn renew_license(&mut self, identifier: &str, new_valid_days: u32) -> bool;
+    fn suspend_person(&mut self, identifier: &str, reason: BlockReason, until_day: Option<u32>) -> bool;
+    fn unsuspend_person(&mut self, identifier: &str, reason: BlockReason) -> bool;
     fn get_person_status(&self, identifier: &str) -> Option<PersonStatus>;
 
+    // Tarife
+    fn register_plan(&mut self, plan: MembershipPlan) -> bool;
+    fn unregister_plan(&mut self, name: &str) -> bool;
+    fn subscribe_person(&mut self, person_id: &str, plan_name: &str) -> bool;
+    fn unsubscribe_person(&mut self, person_id: &str) -> bool;
+
     // Autos
     fn register_car(&mut self, c: Car) -> bool;                       // CHANGED: Car<'a> -> Car
     fn unregister_car(&mut self, identifier: &str) -> bool;
     fn get_car_status(&self, identifier: &str) -> Option<CarStatus>;
     fn get_available_cars(&self) -> Vec<String>;                      // CHANGED: Vec<String> (war bei dir im impl Vec<&str>)
+    fn get_available_cars_at(&self, station_id: &str) -> Vec<String>;
+
+    // Stationen
+    fn register_station(&mut self, s: Station) -> bool;
+    fn unregister_station(&mut self, identifier: &str) -> bool;
+    fn move_car_to_station(&mut self, car_id: &str, station_id: &str) -> bool;
+    fn get_station_occupancy(&self, station_id: &str) -> Option<u32>;
+    fn relocate_car(&mut self, car_id: &str, station_id: &str) -> bool;
 
     // Zusammenspiel Personen/Autos
-    fn reserve_car(&mut self, person_id: &str, car_id: &str, priority: u32) -> bool;  // CHANGED: &'a str -> &str
+    fn reserve_car(&mut self, person_id: &str, car_id: &str) -> bool;  // CHANGED: &'a str -> &str
+    fn reserve_car_between(&mut self, person_id: &str, car_id: &str, start_at: u64, end_at: u64) -> bool;
     fn cancel_reservation(&mut self, person_id: &str, car_id: &str) -> bool;
     fn get_reservations_for_car(&self, car_id: &str) -> Vec<String>;  // CHANGED: Vec<&str> -> Vec<String>
     fn process_reservations(&mut self) -> Vec<(String, String)>;      // CHANGED: Vec<(&'a str,&'a str)> -> Vec<(String,String)>
+    fn reserve_class(&mut self, person_id: &str, filter: CarFilter) -> bool;
+    fn reserve_class_between(&mut self, person_id: &str, filter: CarFilter, start_at: u64, end_at: u64) -> bool;
+    fn cancel_class_reservation(&mut self, id: u32) -> bool;
+    fn get_reservation_history(&self, person_id: &str) -> Vec<ReservationRecord>;
 
     fn rent_car(&mut self, person_id: &str, car_id: &str) -> bool;    // CHANGED: &'a str -> &str
-    fn return_car(&mut self, person_id: &str, car_id: &str, driven_km: u32) -> bool;
+    fn return_car(&mut self, person_id: &str, car_id: &str, driven_km: u32, details: ReturnDetails) -> bool;
+    // Bestätigt die Abholung eines über eine Reservierung übergebenen Autos
+    fn pick_up_car(&mut self, person_id: &str, car_id: &str) -> bool;
+    fn get_charges_for_person(&self, person_id: &str) -> Vec<Charge>;
+
+    // Werkstatt (Wartung/TÜV)
+    fn schedule_maintenance(&mut self, car_id: &str, kind: ServiceKind, day: u32, duration_days: u32) -> bool;
+    fn cancel_scheduled_maintenance(&mut self, car_id: &str, day: u32) -> bool;
+    fn start_maintenance(&mut self, car_id: &str, kind: ServiceKind, duration_days: u32) -> bool;
+    fn extend_maintenance(&mut self, car_id: &str, extra_days: u32) -> bool;
+    fn end_maintenance(&mut self, car_id: &str) -> bool;
+    fn add_service_record(&mut self, record: ServiceRecord) -> bool;
+    fn get_service_history(&self, car_id: &str) -> Vec<ServiceRecord>;
+
+    // Schäden
+    fn get_open_damages(&self, car_id: &str) -> Vec<Damage>;
+    fn get_damages_for_person(&self, person_id: &str) -> Vec<Damage>;
+    fn resolve_damage(&mut self, damage_id: u32) -> bool;
 
     fn simulate_n_days(&mut self, n: u32);
+    fn advance_minutes(&mut self, minutes: u64);
 }
 
 impl CarSharingService for CarSharing {
     // Personen
     fn register_person(&mut self, p: Person) -> bool {
-        if person_exist(&self.persons, &p) == false {
+        if !person_exist(&self.persons, &p) {
             return false;
         } else {
             self.persons.push(p);
@@ -95,7 +919,7 @@ impl CarSharingService for CarSharing {
     }
 
     fn unregister_person(&mut self, identifier: &str) -> bool {
-        if find_index_persons(&self.persons, identifier) == None {
+        if find_index_persons(&self.persons, identifier).is_none() {
             return false;
         }
 
@@ -103,12 +927,21 @@ impl CarSharingService for CarSharing {
             return false;
         }
 
+        for r in self.reservations.clone().iter().filter(|r| r.person_id == identifier) {
+            self.close_record(ReservationRecord::for_car(r), ReservationState::Rejected, "Person abgemeldet".to_string());
+        }
+        for r in self.class_reservations.clone().iter().filter(|r| r.person_id == identifier) {
+            self.close_record(ReservationRecord::for_class(r), ReservationState::Rejected, "Person abgemeldet".to_string());
+        }
+
         self.reservations = self.reservations
             .iter()
             .filter(|r| r.person_id != identifier)
             .cloned()
             .collect();
 
+        self.class_reservations.retain(|r| r.person_id != identifier);
+
         self.persons = self.persons
             .iter()
             .filter(|p| p.identifier != identifier)
@@ -121,24 +954,111 @@ impl CarSharingService for CarSharing {
     fn renew_license(&mut self, identifier: &str, new_valid_days: u32) -> bool {
         if let Some(index) = find_index_persons(&self.persons, identifier) {
             self.persons[index].license_valid_days = new_valid_days;
-            self.persons[index].status = PersonStatus::Active;
+            self.persons[index].grace_days_left = None;
+            // Nur die Sperre wegen des Führerscheins fällt weg, andere Gründe bleiben bestehen
+            self.persons[index].remove_block(BlockReason::LicenseExpired, self.current_day);
+            if !self.persons[index].status.is_blocked() {
+                self.resume_reservations(identifier, "Führerschein verlängert");
+            }
             true
         } else {
             false
         }
     }
 
+    fn suspend_person(&mut self, identifier: &str, reason: BlockReason, until_day: Option<u32>) -> bool {
+        // Der Führerschein sperrt und entsperrt sich selbst (simulate_n_days/renew_license)
+        if reason == BlockReason::LicenseExpired || until_day.is_some_and(|day| day <= self.current_day) {
+            return false;
+        }
+        match find_index_persons(&self.persons, identifier) {
+            Some(index) => {
+                self.persons[index].add_block(reason, self.current_day, until_day);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn unsuspend_person(&mut self, identifier: &str, reason: BlockReason) -> bool {
+        if reason == BlockReason::LicenseExpired {
+            return false;
+        }
+        let index = match find_index_persons(&self.persons, identifier) {
+            Some(index) => index,
+            None => return false,
+        };
+        if !self.persons[index].remove_block(reason, self.current_day) {
+            return false;
+        }
+        if !self.persons[index].status.is_blocked() {
+            self.resume_reservations(identifier, "Sperre aufgehoben");
+        }
+        true
+    }
+
     fn get_person_status(&self, identifier: &str) -> Option<PersonStatus> {
-        if let Some(index) = find_index_persons(&self.persons, identifier) {
-            Some(self.persons[index].status.clone())
-        } else {
-            None
+        find_index_persons(&self.persons, identifier).map(|index| self.persons[index].status.clone())
+    }
+
+    // Tarife
+    fn register_plan(&mut self, plan: MembershipPlan) -> bool {
+        if plan.name.trim().is_empty() || self.plans.iter().any(|p| p.name == plan.name) {
+            return false;
+        }
+        self.plans.push(plan);
+        true
+    }
+
+    fn unregister_plan(&mut self, name: &str) -> bool {
+        // Solange Personen den Tarif gebucht haben, bleibt er bestehen
+        if self.persons.iter().any(|p| p.subscription.as_ref().is_some_and(|s| s.plan == name)) {
+            return false;
+        }
+        match self.plans.iter().position(|p| p.name == name) {
+            Some(index) => {
+                self.plans.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn subscribe_person(&mut self, person_id: &str, plan_name: &str) -> bool {
+        let index = match find_index_persons(&self.persons, person_id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let fee = match self.plans.iter().find(|p| p.name == plan_name) {
+            Some(plan) => plan.monthly_fee_cents,
+            None => return false,
+        };
+        if self.persons[index].subscription.as_ref().is_some_and(|s| s.plan == plan_name) {
+            return false;
+        }
+        // Ein Wechsel gilt sofort und beginnt einen neuen Monat; offene Reservierungen behalten ihre Priorität
+        self.persons[index].subscription = Some(Subscription {
+            plan: plan_name.to_string(),
+            since_day: self.current_day,
+            renewal_day: self.current_day + DAYS_PER_MONTH,
+        });
+        self.bill(person_id, "", ChargeKind::MembershipFee, fee, format!("Monatsgebühr Tarif '{}'", plan_name));
+        true
+    }
+
+    fn unsubscribe_person(&mut self, person_id: &str) -> bool {
+        match find_index_persons(&self.persons, person_id) {
+            Some(index) => self.persons[index].subscription.take().is_some(),
+            None => false,
         }
     }
 
     // Autos
-    fn register_car(&mut self, c: Car) -> bool {
-        if car_exist(&self.cars, &c) == false {
+    fn register_car(&mut self, mut c: Car) -> bool {
+        if !car_exist(&self.cars, &c) {
+            return false;
+        }
+        if c.station_id.as_ref().is_some_and(|id| !station_has_space(&self.stations, &self.cars, id)) {
             return false;
         }
         if c.mileage > 200000 {
@@ -147,13 +1067,14 @@ impl CarSharingService for CarSharing {
         if retirement_score(&c) > 1.0 {
             return false;
         } else {
+            c.registered_day = self.current_day;
             self.cars.push(c);
         }
         true
     }
 
     fn unregister_car(&mut self, identifier: &str) -> bool {
-        if let Some(_) = find_index_cars_rentals(&self.rentals, identifier) {
+        if find_index_cars_rentals(&self.rentals, identifier).is_some() {
             return false;
         }
 
@@ -172,6 +1093,16 @@ impl CarSharingService for CarSharing {
         if matches!(&car.status, CarStatus::Tuv(_)) {
             return false;
         }
+        if matches!(&car.status, CarStatus::Repair(_)) {
+            return false;
+        }
+        if matches!(&car.status, CarStatus::Relocating(_)) {
+            return false;
+        }
+
+        for r in self.reservations.clone().iter().filter(|r| r.car_id == identifier) {
+            self.close_record(ReservationRecord::for_car(r), ReservationState::Rejected, "Auto abgemeldet".to_string());
+        }
 
         self.reservations = self.reservations
             .iter()
@@ -179,6 +1110,8 @@ impl CarSharingService for CarSharing {
             .cloned()
             .collect();
 
+        self.scheduled_services.retain(|s| s.car_id != identifier);
+
         self.cars = self.cars
             .iter()
             .filter(|c| c.identifier != identifier)
@@ -189,40 +1122,113 @@ impl CarSharingService for CarSharing {
     }
 
     fn get_car_status(&self, identifier: &str) -> Option<CarStatus> {
-        if let Some(index) = find_index_cars(&self.cars, identifier) {
-            Some(self.cars[index].status.clone())
-        } else {
-            None
-        }
+        find_index_cars(&self.cars, identifier).map(|index| self.cars[index].status.clone())
     }
 
     fn get_available_cars(&self) -> Vec<String> {
         // CHANGED: Vec<&str> -> Vec<String>
         let mut av_cars: Vec<String> = Vec::new();
         for car in self.cars.iter() {
-            if car.status == CarStatus::Available {
+            if car.status == CarStatus::Available && car.energy_level >= self.policy.min_energy_level {
                 av_cars.push(car.identifier.clone()); // CHANGED: clone String
             }
         }
         av_cars
     }
 
-    fn reserve_car(&mut self, person_id: &str, car_id: &str, priority: u32) -> bool {
-        if p_can_reserve(&self.persons, &self.rentals, &self.reservations, person_id, car_id) {
-            self.reservations.push(Reservation {
-                person_id: person_id.to_string(), // CHANGED: speichern als String
-                car_id: car_id.to_string(),       // CHANGED
-                priority,
-            });
-            true
+    fn get_available_cars_at(&self, station_id: &str) -> Vec<String> {
+        self.get_available_cars()
+            .into_iter()
+            .filter(|id| {
+                find_index_cars(&self.cars, id)
+                    .is_some_and(|index| self.cars[index].station_id.as_deref() == Some(station_id))
+            })
+            .collect()
+    }
+
+    fn register_station(&mut self, s: Station) -> bool {
+        if find_index_stations(&self.stations, &s.identifier).is_some() {
+            return false;
+        }
+        self.stations.push(s);
+        true
+    }
+
+    fn unregister_station(&mut self, identifier: &str) -> bool {
+        let index = if let Some(index) = find_index_stations(&self.stations, identifier) {
+            index
         } else {
-            false
+            return false;
+        };
+        // Nur leere Stationen (auch keine vermieteten Autos, die hierher gehören)
+        if self.cars.iter().any(|c| c.station_id.as_deref() == Some(identifier)) {
+            return false;
+        }
+        self.stations.remove(index);
+        true
+    }
+
+    fn move_car_to_station(&mut self, car_id: &str, station_id: &str) -> bool {
+        let index = if let Some(index) = find_index_cars(&self.cars, car_id) {
+            index
+        } else {
+            return false;
+        };
+        if matches!(self.cars[index].status, CarStatus::Rented | CarStatus::Relocating(_)) {
+            return false;
+        }
+        if self.cars[index].station_id.as_deref() == Some(station_id) {
+            return true;
+        }
+        if !station_has_space(&self.stations, &self.cars, station_id) {
+            return false;
+        }
+        self.cars[index].station_id = Some(station_id.to_string());
+        true
+    }
+
+    fn get_station_occupancy(&self, station_id: &str) -> Option<u32> {
+        find_index_stations(&self.stations, station_id)?;
+        Some(station_occupancy(&self.cars, station_id))
+    }
+
+    // Umsetzen mit Fahrer: das Auto belegt sofort einen Platz am Ziel und ist erst nach der Fahrt wieder verfügbar
+    fn relocate_car(&mut self, car_id: &str, station_id: &str) -> bool {
+        let index = if let Some(index) = find_index_cars(&self.cars, car_id) {
+            index
+        } else {
+            return false;
+        };
+        if self.cars[index].status != CarStatus::Available {
+            return false;
+        }
+        let from = match &self.cars[index].station_id {
+            Some(from) if from != station_id => from.clone(),
+            _ => return false,
+        };
+        if !station_has_space(&self.stations, &self.cars, station_id) {
+            return false;
         }
+
+        let distance_km = station_distance_km(&self.stations, &from, station_id).unwrap_or(0.0);
+        let days = ((distance_km / RELOCATION_KM_PER_DAY).ceil() as u32).max(1);
+        self.cars[index].status = CarStatus::Relocating(days);
+        self.cars[index].station_id = Some(station_id.to_string());
+        true
+    }
+
+    fn reserve_car(&mut self, person_id: &str, car_id: &str) -> bool {
+        self.reserve_car_in(person_id, car_id, None)
+    }
+
+    fn reserve_car_between(&mut self, person_id: &str, car_id: &str, start_at: u64, end_at: u64) -> bool {
+        self.valid_window(start_at, end_at) && self.reserve_car_in(person_id, car_id, Some((start_at, end_at)))
     }
 
     fn cancel_reservation(&mut self, person_id: &str, car_id: &str) -> bool {
         if let Some(index) = find_index_reservations(&self.reservations, person_id, car_id) {
-            self.reservations.remove(index);
+            let r = self.reservations.remove(index);
+            self.close_record(ReservationRecord::for_car(&r), ReservationState::Cancelled, "Vom Kunden storniert".to_string());
             true
         } else {
             false
@@ -236,35 +1242,70 @@ impl CarSharingService for CarSharing {
 
     fn process_reservations(&mut self) -> Vec<(String, String)> {
         // CHANGED: Rückgabe Vec<(String,String)>
-        self.reservations
-            .sort_by(|r0, r1| r1.priority.cmp(&r0.priority));
+        self.sweep_reservations();
 
-        let reservations = self.reservations.clone();
+        // Die Zuteilung übernimmt die Strategie aus der FleetPolicy
+        let assignments = allocate(self, self.policy.allocation);
 
         let mut processed_reservations: Vec<(String, String)> = Vec::new(); // CHANGED
 
-        for r in reservations.iter() {
-            if self.rent_car(&r.person_id, &r.car_id) {
-                processed_reservations.push((r.person_id.clone(), r.car_id.clone())); // CHANGED
+        for (person_id, car_id) in assignments {
+            if self.rent_car(&person_id, &car_id) {
+                processed_reservations.push((person_id, car_id)); // CHANGED
             }
         }
 
-        for (person_id, _) in processed_reservations.iter() {
+        self.reservations_fulfilled += processed_reservations.len() as u32;
+
+        for (person_id, car_id) in processed_reservations.iter() {
+            self.close_fulfilled(person_id, car_id);
             while let Some(index) = find_persons_reservations(&self.reservations, person_id) {
                 self.reservations.remove(index);
             }
+            self.class_reservations.retain(|r| &r.person_id != person_id);
         }
 
         processed_reservations
     }
 
+    fn reserve_class(&mut self, person_id: &str, filter: CarFilter) -> bool {
+        self.reserve_class_in(person_id, filter, None)
+    }
+
+    fn reserve_class_between(&mut self, person_id: &str, filter: CarFilter, start_at: u64, end_at: u64) -> bool {
+        self.valid_window(start_at, end_at) && self.reserve_class_in(person_id, filter, Some((start_at, end_at)))
+    }
+
+    fn cancel_class_reservation(&mut self, id: u32) -> bool {
+        match self.class_reservations.iter().position(|r| r.id == id) {
+            Some(index) => {
+                let r = self.class_reservations.remove(index);
+                self.close_record(ReservationRecord::for_class(&r), ReservationState::Cancelled, "Vom Kunden storniert".to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn get_reservation_history(&self, person_id: &str) -> Vec<ReservationRecord> {
+        self.reservation_history
+            .iter()
+            .filter(|r| r.person_id == person_id)
+            .cloned()
+            .collect()
+    }
+
     fn rent_car(&mut self, person_id: &str, car_id: &str) -> bool {
-        if p_can_rent_car(&self.rentals, &self.persons, &self.cars, person_id, car_id) {
+        if p_can_rent_car(&self.rentals, &self.persons, &self.cars, &self.policy, self.current_day, person_id, car_id)
+            && self.plan_permits(person_id, car_id)
+        {
             self.rentals.push((person_id.to_string(), car_id.to_string())); // CHANGED: speichern als String
 
             if let Some(index) = find_index_cars(&self.cars, car_id) {
                 self.cars[index].status = CarStatus::Rented;
-                self.cars[index].rental_count = self.cars[index].rental_count + 1;
+                self.cars[index].rental_count += 1;
+                self.cars[index].rented_day = Some(self.current_day);
+                self.cars[index].rented_at = Some(self.now());
             }
             true
         } else {
@@ -272,7 +1313,7 @@ impl CarSharingService for CarSharing {
         }
     }
 
-    fn return_car(&mut self, person_id: &str, car_id: &str, driven_km: u32) -> bool {
+    fn return_car(&mut self, person_id: &str, car_id: &str, driven_km: u32, details: ReturnDetails) -> bool {
         let index_rental = if let Some(index_r) = find_index_rentals(&self.rentals, person_id, car_id) {
             index_r
         } else {
@@ -285,83 +1326,322 @@ impl CarSharingService for CarSharing {
             return false;
         };
 
+        // Einwegfahrten: Rückgabe an einer anderen Station, sofern dort Platz ist
+        if let Some(station_id) = &details.station_id {
+            if self.cars[index_car].station_id.as_ref() != Some(station_id) && !station_has_space(&self.stations, &self.cars, station_id) {
+                return false;
+            }
+            self.cars[index_car].station_id = Some(station_id.clone());
+        }
+
+        let ReturnDetails { energy_level, damage, .. } = details;
+
         let start_km = self.cars[index_car].mileage;
-        self.cars[index_car].mileage = self.cars[index_car].mileage + driven_km;
+        self.cars[index_car].mileage += driven_km;
+        self.cars[index_car].driven_km += driven_km;
+
+        if let Some(level) = energy_level {
+            self.cars[index_car].energy_level = level.min(FULL_ENERGY_LEVEL);
+        }
+        let needs_energy = self.cars[index_car].energy_level < self.policy.min_energy_level;
+        if needs_energy {
+            let missing = (FULL_ENERGY_LEVEL - self.cars[index_car].energy_level) as u32;
+            let (kind, fee, label) = match self.cars[index_car].powertrain {
+                Powertrain::Combustion => (ChargeKind::Refuel, self.policy.refuel_fee_cents_per_percent, "Tanken"),
+                Powertrain::Electric => (ChargeKind::Recharge, self.policy.recharge_fee_cents_per_percent, "Laden"),
+            };
+            self.bill(person_id, car_id, kind, missing.saturating_mul(fee), format!("{} von Auto '{}'", label, car_id));
+        }
+        // Kilometer- und Zeitpreis nach dem Tarif, der bei der Rückgabe gilt; Beträge über u32::MAX werden gekappt
+        let plan = find_index_persons(&self.persons, person_id).and_then(|index| person_plan(&self.plans, &self.persons[index]));
+        let per_km = plan.map_or(0, |plan| plan.per_km_cents);
+        let per_hour = plan.and_then(|plan| plan.per_hour_cents).unwrap_or(self.policy.per_hour_cents);
+        self.bill(person_id, car_id, ChargeKind::Mileage, driven_km.saturating_mul(per_km), format!("{} km mit Auto '{}'", driven_km, car_id));
+        let hours = billed_hours(rental_start(&self.cars[index_car]).unwrap_or(self.now()), self.now());
+        self.bill(person_id, car_id, ChargeKind::RentalTime, hours.saturating_mul(per_hour), format!("{} Std. mit Auto '{}'", hours, car_id));
+
+        let repair_days = damage.as_ref().map(|d| d.repair_days.unwrap_or(default_repair_days(d.severity))).unwrap_or(0);
+
+        // Bei voller Werkstatt wird eine fällige Wartung als Termin eingereiht statt sofort begonnen
+        let mut service = check_maintenance_or_tuv(start_km, driven_km);
+        match &service {
+            Some(status) if service_kind(status).is_some() && !workshop_has_capacity(&self.cars, &self.policy) => {
+                self.queue_service(car_id, status);
+                service = None;
+            }
+            _ => {}
+        }
+
+        if let Some(report) = damage {
+            let block = self.policy.block_on_damage.is_some_and(|min| report.severity >= min);
+            match find_index_persons(&self.persons, person_id) {
+                Some(index) if block => self.persons[index].add_block(BlockReason::Damage, self.current_day, None),
+                _ => {}
+            }
+            self.damages.push(Damage {
+                id: self.damages.iter().map(|d| d.id).max().map_or(1, |id| id + 1),
+                car_id: car_id.to_string(),
+                person_id: person_id.to_string(),
+                reported_day: self.current_day,
+                severity: report.severity,
+                description: report.description,
+                location: report.location,
+                resolved_day: None,
+            });
+        }
 
         if retirement_score(&self.cars[index_car]) > 1.0 {
             self.cars[index_car].status = CarStatus::Retired;
-        } else if let Some(new_state) = check_maintenance_or_tuv(start_km, driven_km) {
+        } else if repair_days > 0 {
+            // Reparatur geht vor; eine fällige Wartung wird als Termin eingereiht und danach begonnen
+            if let Some(status) = &service {
+                self.queue_service(car_id, status);
+            }
+            self.cars[index_car].status = CarStatus::Repair(repair_days);
+        } else if let Some(new_state) = service {
             self.cars[index_car].status = new_state;
+            self.cars[index_car].service_started_day = Some(self.current_day);
+        } else if needs_energy {
+            self.cars[index_car].status = match self.cars[index_car].powertrain {
+                Powertrain::Combustion => CarStatus::Charging(REFUEL_DAYS),
+                Powertrain::Electric => CarStatus::Charging(RECHARGE_DAYS),
+            };
         } else {
             self.cars[index_car].status = CarStatus::Available;
         }
 
+        // Wer zurückgibt, hat das Auto abgeholt; ohne gefahrene km war die Reservierung ein No-Show.
+        // Eine direkte Ausleihe hat keine Reservierung und zählt nie als No-Show.
+        if let Some(index) = self.reserved_rental(person_id, car_id) {
+            self.reservation_history[index].pickup_due_day = None;
+            self.reservation_history[index].rental_open = false;
+            if driven_km == 0 {
+                let reason = if self.cars[index_car].rented_day == Some(self.current_day) {
+                    "Auto sofort ohne Fahrt zurückgegeben"
+                } else {
+                    "Auto nicht abgeholt"
+                };
+                self.record_no_show(index, reason);
+            }
+        }
+
+        self.cars[index_car].rented_day = None;
+        self.cars[index_car].rented_at = None;
         self.rentals.remove(index_rental);
         true
     }
 
-    fn simulate_n_days(&mut self, n: u32) {
-        let mut days = n;
+    fn pick_up_car(&mut self, person_id: &str, car_id: &str) -> bool {
+        if find_index_rentals(&self.rentals, person_id, car_id).is_none() {
+            return false;
+        }
+        match self.awaiting_pickup(person_id, car_id) {
+            Some(index) => {
+                self.reservation_history[index].pickup_due_day = None;
+                true
+            }
+            None => false,
+        }
+    }
 
-        while days > 0 {
-            self.current_day = self.current_day + 1;
+    fn schedule_maintenance(&mut self, car_id: &str, kind: ServiceKind, day: u32, duration_days: u32) -> bool {
+        if day <= self.current_day || duration_days == 0 {
+            return false;
+        }
+        match find_index_cars(&self.cars, car_id) {
+            Some(index) if self.cars[index].status != CarStatus::Retired => {}
+            _ => return false,
+        }
+        if find_index_scheduled(&self.scheduled_services, car_id, day).is_some() {
+            return false;
+        }
 
-            for p in self.persons.iter_mut() {
-                if p.license_valid_days > 0 {
-                    p.license_valid_days = p.license_valid_days - 1
-                }
-                if p.license_valid_days == 0 {
-                    p.status = PersonStatus::Blocked;
-                }
-            }
+        self.scheduled_services.push(ScheduledService {
+            car_id: car_id.to_string(),
+            kind,
+            day,
+            duration_days,
+        });
+        true
+    }
 
-            for c in self.cars.iter_mut() {
-                c.age_days = c.age_days + 1;
-            }
+    fn cancel_scheduled_maintenance(&mut self, car_id: &str, day: u32) -> bool {
+        if let Some(index) = find_index_scheduled(&self.scheduled_services, car_id, day) {
+            self.scheduled_services.remove(index);
+            true
+        } else {
+            false
+        }
+    }
 
-            for c in self.cars.iter_mut() {
-                if c.age_days == MAX_AGE_DAYS && c.status != CarStatus::Rented {
-                    c.status = CarStatus::Retired
-                }
-                if c.rental_count == MAX_RENTALS && c.status != CarStatus::Rented {
-                    c.status = CarStatus::Retired
-                }
-                if retirement_score(c) > 1.0 && c.status != CarStatus::Rented {
-                    c.status = CarStatus::Retired
-                }
+    fn start_maintenance(&mut self, car_id: &str, kind: ServiceKind, duration_days: u32) -> bool {
+        if duration_days == 0 || !workshop_has_capacity(&self.cars, &self.policy) {
+            return false;
+        }
+        let index = if let Some(index) = find_index_cars(&self.cars, car_id) {
+            index
+        } else {
+            return false;
+        };
+        if self.cars[index].status != CarStatus::Available {
+            return false;
+        }
+
+        self.cars[index].status = service_status(kind, duration_days);
+        self.cars[index].service_started_day = Some(self.current_day);
+        true
+    }
+
+    fn extend_maintenance(&mut self, car_id: &str, extra_days: u32) -> bool {
+        let index = if let Some(index) = find_index_cars(&self.cars, car_id) {
+            index
+        } else {
+            return false;
+        };
+
+        let extended = match self.cars[index].status.clone() {
+            CarStatus::Maintenance(days_left) => days_left.checked_add(extra_days).map(CarStatus::Maintenance),
+            CarStatus::Tuv(days_left) => days_left.checked_add(extra_days).map(CarStatus::Tuv),
+            _ => None,
+        };
+        match extended {
+            Some(status) => {
+                self.cars[index].status = status;
+                true
             }
+            None => false,
+        }
+    }
 
-            // CHANGED: status.clone(), damit wir nicht aus &mut c.status "raus-moven"
-            for c in self.cars.iter_mut() {
-                if let CarStatus::Maintenance(days_left) = c.status.clone() {
-                    if days_left > 1 {
-                        c.status = CarStatus::Maintenance(days_left - 1);
-                    } else {
-                        c.status = CarStatus::Available;
-                    }
-                }
+    fn end_maintenance(&mut self, car_id: &str) -> bool {
+        let index = if let Some(index) = find_index_cars(&self.cars, car_id) {
+            index
+        } else {
+            return false;
+        };
+
+        if let Some(kind) = service_kind(&self.cars[index].status) {
+            let record = finished_service_record(&self.cars[index], kind, self.current_day);
+            self.service_records.push(record);
+            self.cars[index].status = CarStatus::Available;
+            self.cars[index].service_started_day = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn add_service_record(&mut self, record: ServiceRecord) -> bool {
+        if find_index_cars(&self.cars, &record.car_id).is_none() {
+            return false;
+        }
+        if record.start_day > record.end_day || record.end_day > self.current_day {
+            return false;
+        }
+        self.service_records.push(record);
+        true
+    }
+
+    fn get_service_history(&self, car_id: &str) -> Vec<ServiceRecord> {
+        let mut history: Vec<ServiceRecord> = self.service_records
+            .iter()
+            .filter(|r| r.car_id == car_id)
+            .cloned()
+            .collect();
+        history.sort_by_key(|r| r.start_day);
+        history
+    }
+
+    fn get_open_damages(&self, car_id: &str) -> Vec<Damage> {
+        self.damages
+            .iter()
+            .filter(|d| d.car_id == car_id && d.resolved_day.is_none())
+            .cloned()
+            .collect()
+    }
+
+    fn get_charges_for_person(&self, person_id: &str) -> Vec<Charge> {
+        self.charges
+            .iter()
+            .filter(|c| c.person_id == person_id)
+            .cloned()
+            .collect()
+    }
+
+    fn get_damages_for_person(&self, person_id: &str) -> Vec<Damage> {
+        self.damages
+            .iter()
+            .filter(|d| d.person_id == person_id)
+            .cloned()
+            .collect()
+    }
+
+    fn resolve_damage(&mut self, damage_id: u32) -> bool {
+        match self.damages.iter_mut().find(|d| d.id == damage_id && d.resolved_day.is_none()) {
+            Some(damage) => {
+                damage.resolved_day = Some(self.current_day);
+                true
             }
+            None => false,
+        }
+    }
 
-            // CHANGED: status.clone() auch hier
-            for c in self.cars.iter_mut() {
-                if let CarStatus::Tuv(days_left) = c.status.clone() {
-                    if days_left > 1 {
-                        c.status = CarStatus::Tuv(days_left - 1);
-                    } else {
-                        c.status = CarStatus::Available;
-                    }
+    // Ganze Tage laufen über die Uhr, damit Zeitfenster innerhalb der Tage bedient werden
+    fn simulate_n_days(&mut self, n: u32) {
+        self.advance_minutes(n as u64 * MINUTES_PER_DAY);
+    }
+
+    fn advance_minutes(&mut self, minutes: u64) {
+        if minutes == 0 {
+            return;
+        }
+        let target = self.now() + minutes;
+        // Zeitpunkt der letzten Zuteilung; zur selben Minute wird nicht noch einmal zugeteilt
+        let mut served_at = None;
+        loop {
+            let midnight = (self.current_day as u64 + 1) * MINUTES_PER_DAY;
+            match self.next_window_start(target) {
+                // Zeitfenster werden zu ihrem Beginn bedient, nicht erst am Ende des Schritts
+                Some(start) if start < midnight => {
+                    self.minute_of_day = (start - self.current_day as u64 * MINUTES_PER_DAY) as u32;
+                    self.process_reservations();
+                    served_at = Some(start);
+                }
+                // Jede überschrittene Mitternacht ist ein vollständiger Tageswechsel
+                _ if midnight <= target => {
+                    self.minute_of_day = 0;
+                    self.run_day();
+                    served_at = Some(midnight);
                 }
+                _ => break,
             }
-
+        }
+        self.minute_of_day = (target - self.current_day as u64 * MINUTES_PER_DAY) as u32;
+        if served_at != Some(target) {
             self.process_reservations();
-            days = days - 1;
         }
     }
 }
 
 // -------------------- Hilfsfunktionen --------------------
 
-fn find_index_rentals(rentals: &Vec<(String, String)>, person_id: &str, car_id: &str) -> Option<usize> {
+/// Zeitpunkt als "Tag N, HH:MM"
+pub fn format_time(at: u64) -> String {
+    let minute = at % MINUTES_PER_DAY;
+    format!("Tag {}, {:02}:{:02}", at / MINUTES_PER_DAY, minute / MINUTES_PER_HOUR, minute % MINUTES_PER_HOUR)
+}
+
+// Beginn der laufenden Ausleihe; ältere Stände kennen nur den Tag
+fn rental_start(car: &Car) -> Option<u64> {
+    car.rented_at.or(car.rented_day.map(|day| day as u64 * MINUTES_PER_DAY))
+}
+
+// Abgerechnet wird jede angefangene Stunde, mindestens eine
+fn billed_hours(start: u64, end: u64) -> u32 {
+    end.saturating_sub(start).div_ceil(MINUTES_PER_HOUR).max(1) as u32
+}
+
+fn find_index_rentals(rentals: &[(String, String)], person_id: &str, car_id: &str) -> Option<usize> {
     // CHANGED: rentals type Vec<(String,String)>
     for (index, rental) in rentals.iter().enumerate() {
         if rental.0 == person_id && rental.1 == car_id {
@@ -371,7 +1651,7 @@ fn find_index_rentals(rentals: &Vec<(String, String)>, person_id: &str, car_id:
     None
 }
 
-fn find_persons_reservations(reservations: &Vec<Reservation>, person_id: &str) -> Option<usize> {
+fn find_persons_reservations(reservations: &[Reservation], person_id: &str) -> Option<usize> {
     // CHANGED: Reservation ohne Lifetime
     for (index, reservation) in reservations.iter().enumerate() {
         if reservation.person_id == person_id {
@@ -381,7 +1661,7 @@ fn find_persons_reservations(reservations: &Vec<Reservation>, person_id: &str) -
     None
 }
 
-fn find_persons_rentals(rentals: &Vec<(String, String)>, person_id: &str) -> bool {
+fn find_persons_rentals(rentals: &[(String, String)], person_id: &str) -> bool {
     // CHANGED: rentals type
     for rental in rentals.iter() {
         if rental.0 == person_id {
@@ -392,11 +1672,13 @@ fn find_persons_rentals(rentals: &Vec<(String, String)>, person_id: &str) -> boo
 }
 
 fn p_can_rent_car(
-    rentals: &Vec<(String, String)>, // CHANGED
-    persons: &Vec<Person>,           // CHANGED
-    cars: &Vec<Car>,                 // CHANGED
-    person_id: &str,                 // CHANGED
-    car_id: &str,                    // CHANGED
+    rentals: &[(String, String)], // CHANGED
+    persons: &[Person],           // CHANGED
+    cars: &[Car],                 // CHANGED
+    policy: &FleetPolicy,
+    current_day: u32,
+    person_id: &str,              // CHANGED
+    car_id: &str,                 // CHANGED
 ) -> bool {
     for p in rentals.iter() {
         if person_id == p.0 {
@@ -405,7 +1687,7 @@ fn p_can_rent_car(
     }
 
     for p in persons.iter() {
-        if p.identifier == person_id && p.status == PersonStatus::Blocked {
+        if p.identifier == person_id && p.status.is_blocked() {
             return false
         }
     }
@@ -414,11 +1696,47 @@ fn p_can_rent_car(
         if c.identifier == car_id && c.status != CarStatus::Available {
             return false
         }
+        if c.identifier == car_id && c.energy_level < policy.min_energy_level {
+            return false
+        }
+    }
+
+    let person = persons.iter().find(|p| p.identifier == person_id);
+    let car = cars.iter().find(|c| c.identifier == car_id);
+    match (person, car) {
+        (Some(p), Some(c)) if !may_drive(p, c, policy, current_day) => return false,
+        _ => {}
     }
     true
 }
 
-fn persons_with_reservation_for_car(reservations: &Vec<Reservation>, car_id: &str) -> Vec<String> {
+/// Führerscheinklasse, die für ein Auto dieser Klasse nötig ist
+pub fn required_license(policy: &FleetPolicy, class: CarClass) -> LicenseClass {
+    policy
+        .license_requirements
+        .iter()
+        .find(|(k, _)| *k == class)
+        .map_or(LicenseClass::B, |(_, license)| *license)
+}
+
+/// Gebuchter Tarif einer Person (None = ohne Mitgliedschaft)
+pub fn person_plan<'a>(plans: &'a [MembershipPlan], person: &Person) -> Option<&'a MembershipPlan> {
+    person.subscription.as_ref().and_then(|s| plans.iter().find(|p| p.name == s.plan))
+}
+
+/// Fahrzeugklasse im Tarif enthalten; ohne Tarif sind alle Klassen erlaubt
+pub fn plan_allows(plans: &[MembershipPlan], person: &Person, car: &Car) -> bool {
+    person_plan(plans, person).is_none_or(|plan| plan.allows(car.class))
+}
+
+/// Passende Führerscheinklasse und Mindestalter
+pub fn may_drive(person: &Person, car: &Car, policy: &FleetPolicy, current_day: u32) -> bool {
+    let required = required_license(policy, car.class);
+    person.license_classes.iter().any(|l| l.covers(required))
+        && person.age_years(current_day).is_none_or(|age| age >= policy.min_age_years)
+}
+
+fn persons_with_reservation_for_car(reservations: &[Reservation], car_id: &str) -> Vec<String> {
     // CHANGED: Vec<&str> -> Vec<String>
     reservations
         .iter()
@@ -428,14 +1746,14 @@ fn persons_with_reservation_for_car(reservations: &Vec<Reservation>, car_id: &st
 }
 
 fn p_can_reserve(
-    persons: &Vec<Person>,               // CHANGED
-    rentals: &Vec<(String, String)>,     // CHANGED
-    reservations: &Vec<Reservation>,     // CHANGED
+    persons: &[Person],               // CHANGED
+    rentals: &[(String, String)],     // CHANGED
+    reservations: &[Reservation],     // CHANGED
     person_id: &str,
     car_id: &str,                        // CHANGED: &'a str -> &str
 ) -> bool {
     for p in persons.iter() {
-        if p.identifier == person_id && p.status == PersonStatus::Blocked {
+        if p.identifier == person_id && p.status.is_blocked() {
             return false;
         }
     }
@@ -454,7 +1772,126 @@ fn p_can_reserve(
     true
 }
 
-fn find_index_reservations(reservations: &Vec<Reservation>, person_id: &str, car_id: &str) -> Option<usize> {
+fn p_can_reserve_class(
+    persons: &[Person],
+    rentals: &[(String, String)],
+    class_reservations: &[ClassReservation],
+    person_id: &str,
+    filter: &CarFilter,
+) -> bool {
+    if persons.iter().any(|p| p.identifier == person_id && p.status.is_blocked()) {
+        return false;
+    }
+    if find_persons_rentals(rentals, person_id) {
+        return false;
+    }
+    !class_reservations.iter().any(|r| r.person_id == person_id && &r.filter == filter)
+}
+
+fn find_index_scheduled(scheduled: &[ScheduledService], car_id: &str, day: u32) -> Option<usize> {
+    scheduled.iter().position(|s| s.car_id == car_id && s.day == day)
+}
+
+fn service_status(kind: ServiceKind, duration_days: u32) -> CarStatus {
+    match kind {
+        ServiceKind::Maintenance => CarStatus::Maintenance(duration_days),
+        ServiceKind::Tuv => CarStatus::Tuv(duration_days),
+    }
+}
+
+fn service_kind(status: &CarStatus) -> Option<ServiceKind> {
+    match status {
+        CarStatus::Maintenance(_) => Some(ServiceKind::Maintenance),
+        CarStatus::Tuv(_) => Some(ServiceKind::Tuv),
+        _ => None,
+    }
+}
+
+// Automatischer Nachweis, wenn ein Auto die Werkstatt verlässt (ohne Notizen und Kosten)
+fn finished_service_record(car: &Car, kind: ServiceKind, end_day: u32) -> ServiceRecord {
+    ServiceRecord {
+        car_id: car.identifier.clone(),
+        kind,
+        start_day: car.service_started_day.unwrap_or(end_day),
+        end_day,
+        mileage: car.mileage,
+        notes: String::new(),
+        cost_cents: 0,
+    }
+}
+
+// Stationen
+fn find_index_stations(stations: &[Station], identifier: &str) -> Option<usize> {
+    stations.iter().position(|s| s.identifier == identifier)
+}
+
+// Belegte Plätze: alle nicht ausgemusterten Autos der Station.
+// Vermietete Autos halten ihren Platz, damit die Rückgabe an die Heimatstation immer möglich ist.
+fn station_occupancy(cars: &[Car], station_id: &str) -> u32 {
+    cars.iter()
+        .filter(|c| c.station_id.as_deref() == Some(station_id))
+        .filter(|c| c.status != CarStatus::Retired)
+        .count() as u32
+}
+
+fn station_has_space(stations: &[Station], cars: &[Car], station_id: &str) -> bool {
+    match find_index_stations(stations, station_id) {
+        Some(index) => station_occupancy(cars, station_id) < stations[index].capacity,
+        None => false,
+    }
+}
+
+/// Entfernung zweier Punkte auf der Erdoberfläche in km
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Luftlinie zwischen zwei Stationen (None, wenn eine davon unbekannt ist)
+pub fn station_distance_km(stations: &[Station], from: &str, to: &str) -> Option<f64> {
+    let a = &stations[find_index_stations(stations, from)?];
+    let b = &stations[find_index_stations(stations, to)?];
+    Some(haversine_km(a.latitude, a.longitude, b.latitude, b.longitude))
+}
+
+fn full_energy_level() -> u8 {
+    FULL_ENERGY_LEVEL
+}
+
+fn default_seats() -> u8 {
+    DEFAULT_SEATS
+}
+
+fn default_license_classes() -> Vec<LicenseClass> {
+    vec![LicenseClass::B]
+}
+
+fn default_repair_days(severity: DamageSeverity) -> u32 {
+    match severity {
+        DamageSeverity::Minor => 0,
+        DamageSeverity::Major => REPAIR_DAYS_MAJOR,
+        DamageSeverity::Breakdown => REPAIR_DAYS_BREAKDOWN,
+    }
+}
+
+// Werkstatt: Anzahl Autos, die gerade in Wartung, beim TÜV oder in Reparatur sind
+fn workshop_occupancy(cars: &[Car]) -> u32 {
+    cars.iter()
+        .filter(|c| matches!(c.status, CarStatus::Maintenance(_) | CarStatus::Tuv(_) | CarStatus::Repair(_)))
+        .count() as u32
+}
+
+fn workshop_has_capacity(cars: &[Car], policy: &FleetPolicy) -> bool {
+    match policy.workshop_capacity {
+        Some(capacity) => workshop_occupancy(cars) < capacity,
+        None => true,
+    }
+}
+
+fn find_index_reservations(reservations: &[Reservation], person_id: &str, car_id: &str) -> Option<usize> {
     // CHANGED
     for (index, reservation) in reservations.iter().enumerate() {
         if reservation.person_id == person_id && reservation.car_id == car_id {
@@ -465,7 +1902,7 @@ fn find_index_reservations(reservations: &Vec<Reservation>, person_id: &str, car
 }
 
 // Cars
-fn car_exist(cars: &Vec<Car>, c: &Car) -> bool {
+fn car_exist(cars: &[Car], c: &Car) -> bool {
     // CHANGED
     for car in cars.iter() {
         if c.identifier == car.identifier {
@@ -488,14 +1925,14 @@ fn check_maintenance_or_tuv(mileage: u32, driven_km: u32) -> Option<CarStatus> {
     }
 }
 
-fn retirement_score(car: &Car) -> f32 {
+pub fn retirement_score(car: &Car) -> f32 {
     // CHANGED
     (car.age_days as f32 / MAX_AGE_DAYS as f32)
         + (car.mileage as f32 / MAX_KM as f32)
         + (car.rental_count as f32 / MAX_RENTALS as f32)
 }
 
-fn find_index_cars_rentals(rentals: &Vec<(String, String)>, car_id: &str) -> Option<usize> {
+fn find_index_cars_rentals(rentals: &[(String, String)], car_id: &str) -> Option<usize> {
     // CHANGED
     for (index, rental) in rentals.iter().enumerate() {
         if rental.1 == car_id {
@@ -505,7 +1942,7 @@ fn find_index_cars_rentals(rentals: &Vec<(String, String)>, car_id: &str) -> Opt
     None
 }
 
-fn find_index_cars(cars: &Vec<Car>, identifier: &str) -> Option<usize> {
+fn find_index_cars(cars: &[Car], identifier: &str) -> Option<usize> {
     // CHANGED
     for (index, car) in cars.iter().enumerate() {
         if identifier == car.identifier {
@@ -516,7 +1953,7 @@ fn find_index_cars(cars: &Vec<Car>, identifier: &str) -> Option<usize> {
 }
 
 // Personen
-fn person_exist(persons: &Vec<Person>, p: &Person) -> bool {
+fn person_exist(persons: &[Person], p: &Person) -> bool {
     // CHANGED
     for person in persons.iter() {
         if p.identifier == person.identifier {
@@ -526,7 +1963,7 @@ fn person_exist(persons: &Vec<Person>, p: &Person) -> bool {
     true
 }
 
-fn find_index_persons(persons: &Vec<Person>, identifier: &str) -> Option<usize> {
+fn find_index_persons(persons: &[Person], identifier: &str) -> Option<usize> {
     // CHANGED
     for (index, person) in persons.iter().enumerate() {
         if identifier == person.identifier {
@@ -536,7 +1973,751 @@ fn find_index_persons(persons: &Vec<Person>, identifier: &str) -> Option<usize>
     None
 }
 
+impl Default for CarSharing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CarSharing {
+    // Startet fällige Werkstatttermine, solange die Werkstatt Kapazität hat.
+    // Termine für nicht verfügbare Autos oder bei voller Werkstatt bleiben bis zum nächsten Tag stehen.
+    fn start_scheduled_maintenance(&mut self) {
+        let mut due: Vec<ScheduledService> = self.scheduled_services
+            .iter()
+            .filter(|s| s.day <= self.current_day)
+            .cloned()
+            .collect();
+        due.sort_by_key(|s| s.day);
+
+        for s in due.iter() {
+            let index = match find_index_cars(&self.cars, &s.car_id) {
+                Some(index) => index,
+                None => continue,
+            };
+            let done = match self.cars[index].status {
+                CarStatus::Available if workshop_has_capacity(&self.cars, &self.policy) => {
+                    self.cars[index].status = service_status(s.kind, s.duration_days);
+                    self.cars[index].service_started_day = Some(self.current_day);
+                    true
+                }
+                // Ausgemusterte Autos brauchen keinen Termin mehr
+                CarStatus::Retired => true,
+                _ => false,
+            };
+            if done {
+                self.scheduled_services.retain(|x| !(x.car_id == s.car_id && x.day == s.day));
+            }
+        }
+    }
+
+    // Reiht eine fällige Wartung bzw. einen fälligen TÜV als Termin für heute ein;
+    // start_scheduled_maintenance beginnt ihn, sobald das Auto verfügbar ist und die Werkstatt Platz hat
+    fn queue_service(&mut self, car_id: &str, status: &CarStatus) {
+        let (kind, duration_days) = match status {
+            CarStatus::Maintenance(days) => (ServiceKind::Maintenance, *days),
+            CarStatus::Tuv(days) => (ServiceKind::Tuv, *days),
+            _ => return,
+        };
+        if find_index_scheduled(&self.scheduled_services, car_id, self.current_day).is_none() {
+            self.scheduled_services.push(ScheduledService {
+                car_id: car_id.to_string(),
+                kind,
+                day: self.current_day,
+                duration_days,
+            });
+        }
+    }
+
+    /// Letzter Tag, an dem die Person das übergebene Auto abholen muss (None = schon abgeholt oder keine Frist)
+    pub fn pickup_due_day(&self, person_id: &str, car_id: &str) -> Option<u32> {
+        self.awaiting_pickup(person_id, car_id).and_then(|index| self.reservation_history[index].pickup_due_day)
+    }
+
+    // Tageswechsel um Mitternacht: Fristen, Sperren, Werkstatt und Umsetzungen zählen einen Tag weiter,
+    // danach werden Reservierungen bedient und die Tageswerte festgehalten
+    fn run_day(&mut self) {
+        self.current_day += 1;
+
+        let expired_before = self.persons.iter().filter(|p| p.has_block(BlockReason::LicenseExpired)).count() as u32;
+        let retired_before = status_counts(&self.cars).retired;
+
+        let mut unblocked: Vec<String> = Vec::new();
+        let mut notices: Vec<(String, NotificationKind, String)> = Vec::new();
+        for p in self.persons.iter_mut() {
+            if p.lift_expired_blocks(self.current_day) {
+                unblocked.push(p.identifier.clone());
+            }
+            if p.license_valid_days > 0 {
+                p.license_valid_days -= 1;
+                if p.license_valid_days > 0 && self.policy.license_reminder_days.contains(&p.license_valid_days) {
+                    notices.push((
+                        p.identifier.clone(),
+                        NotificationKind::LicenseExpiring,
+                        match p.license_valid_days {
+                            1 => "Ihr Führerschein läuft morgen ab.".to_string(),
+                            days => format!("Ihr Führerschein läuft in {} Tagen ab.", days),
+                        },
+                    ));
+                }
+                if p.license_valid_days == 0 && self.policy.license_grace_days > 0 {
+                    p.grace_days_left = Some(self.policy.license_grace_days);
+                    notices.push((
+                        p.identifier.clone(),
+                        NotificationKind::LicenseGracePeriod,
+                        format!("Ihr Führerschein ist abgelaufen. Sie werden in {} Tagen gesperrt.", self.policy.license_grace_days),
+                    ));
+                    continue;
+                }
+            } else if let Some(days_left) = p.grace_days_left {
+                p.grace_days_left = if days_left > 1 { Some(days_left - 1) } else { None };
+            }
+            if p.license_valid_days == 0 && p.grace_days_left.is_none() && !p.has_block(BlockReason::LicenseExpired) {
+                notices.push((
+                    p.identifier.clone(),
+                    NotificationKind::LicenseExpired,
+                    "Ihr Führerschein ist abgelaufen. Sie sind bis zur Verlängerung gesperrt.".to_string(),
+                ));
+                p.add_block(BlockReason::LicenseExpired, self.current_day, None);
+            }
+        }
+        // Wer heute noch gesperrt ist (z.B. Führerschein), bleibt es
+        for person_id in unblocked {
+            if find_index_persons(&self.persons, &person_id).is_some_and(|index| !self.persons[index].status.is_blocked()) {
+                self.resume_reservations(&person_id, "Sperre abgelaufen");
+            }
+        }
+        if let Some(due_days) = self.policy.rental_due_days {
+            for (person_id, car_id) in self.rentals.iter() {
+                let due_today = find_index_cars(&self.cars, car_id)
+                    .and_then(|index| self.cars[index].rented_day)
+                    .is_some_and(|day| self.current_day - day == due_days);
+                if due_today {
+                    notices.push((
+                        person_id.clone(),
+                        NotificationKind::CarDueBack,
+                        format!("Bitte geben Sie Auto '{}' heute zurück.", car_id),
+                    ));
+                }
+            }
+        }
+        for (person_id, kind, message) in notices {
+            self.notify(&person_id, kind, message);
+        }
+        self.renew_subscriptions();
+
+        for c in self.cars.iter_mut() {
+            c.age_days += 1;
+        }
+
+        for c in self.cars.iter_mut() {
+            if c.age_days == MAX_AGE_DAYS && c.status != CarStatus::Rented {
+                c.status = CarStatus::Retired
+            }
+            if c.rental_count == MAX_RENTALS && c.status != CarStatus::Rented {
+                c.status = CarStatus::Retired
+            }
+            if retirement_score(c) > 1.0 && c.status != CarStatus::Rented {
+                c.status = CarStatus::Retired
+            }
+        }
+
+        // CHANGED: status.clone(), damit wir nicht aus &mut c.status "raus-moven"
+        for c in self.cars.iter_mut() {
+            if let CarStatus::Maintenance(days_left) = c.status.clone() {
+                if days_left > 1 {
+                    c.status = CarStatus::Maintenance(days_left - 1);
+                } else {
+                    self.service_records.push(finished_service_record(c, ServiceKind::Maintenance, self.current_day));
+                    c.status = CarStatus::Available;
+                    c.service_started_day = None;
+                    c.energy_level = FULL_ENERGY_LEVEL;
+                }
+            }
+        }
+
+        // CHANGED: status.clone() auch hier
+        for c in self.cars.iter_mut() {
+            if let CarStatus::Tuv(days_left) = c.status.clone() {
+                if days_left > 1 {
+                    c.status = CarStatus::Tuv(days_left - 1);
+                } else {
+                    self.service_records.push(finished_service_record(c, ServiceKind::Tuv, self.current_day));
+                    c.status = CarStatus::Available;
+                    c.service_started_day = None;
+                    c.energy_level = FULL_ENERGY_LEVEL;
+                }
+            }
+        }
+
+        // Nach der Reparatur gelten alle Schäden ab Major als behoben
+        for c in self.cars.iter_mut() {
+            if let CarStatus::Repair(days_left) = c.status.clone() {
+                if days_left > 1 {
+                    c.status = CarStatus::Repair(days_left - 1);
+                } else {
+                    c.status = CarStatus::Available;
+                    c.energy_level = FULL_ENERGY_LEVEL;
+                    for d in self.damages.iter_mut() {
+                        if d.car_id == c.identifier && d.resolved_day.is_none() && d.severity >= DamageSeverity::Major {
+                            d.resolved_day = Some(self.current_day);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Tanken bzw. Laden
+        for c in self.cars.iter_mut() {
+            if let CarStatus::Charging(days_left) = c.status.clone() {
+                if days_left > 1 {
+                    c.status = CarStatus::Charging(days_left - 1);
+                } else {
+                    c.status = CarStatus::Available;
+                    c.energy_level = FULL_ENERGY_LEVEL;
+                }
+            }
+        }
+
+        // Umsetzungen zwischen Stationen
+        for c in self.cars.iter_mut() {
+            if let CarStatus::Relocating(days_left) = c.status.clone() {
+                if days_left > 1 {
+                    c.status = CarStatus::Relocating(days_left - 1);
+                } else {
+                    c.status = CarStatus::Available;
+                }
+            }
+        }
+
+        self.release_unclaimed_cars();
+        self.start_scheduled_maintenance();
+
+        let fulfilled = self.process_reservations();
+
+        let cars = status_counts(&self.cars);
+        let expired_after = self.persons.iter().filter(|p| p.has_block(BlockReason::LicenseExpired)).count() as u32;
+        self.history.push(DailySnapshot {
+            day: self.current_day,
+            active_rentals: self.rentals.len() as u32,
+            reservations_pending: (self.reservations.len() + self.class_reservations.len()) as u32,
+            reservations_fulfilled: fulfilled.len() as u32,
+            licenses_expired: expired_after.saturating_sub(expired_before),
+            cars_retired: cars.retired.saturating_sub(retired_before),
+            cars,
+        });
+    }
+
+    /// Aktueller Zeitpunkt in Minuten seit Beginn von Tag 0
+    pub fn now(&self) -> u64 {
+        self.current_day as u64 * MINUTES_PER_DAY + self.minute_of_day as u64
+    }
+
+    // Gemeinsamer Teil von reserve_car und reserve_car_between
+    fn reserve_car_in(&mut self, person_id: &str, car_id: &str, window: Option<(u64, u64)>) -> bool {
+        // Autos, die die Person nicht fahren darf oder die der Tarif nicht enthält, können auch nicht reserviert werden
+        let allowed = match (find_index_persons(&self.persons, person_id), find_index_cars(&self.cars, car_id)) {
+            (Some(p), Some(c)) => may_drive(&self.persons[p], &self.cars[c], &self.policy, self.current_day),
+            _ => true,
+        };
+        if allowed && self.plan_permits(person_id, car_id) && self.within_reservation_quota(person_id) && p_can_reserve(&self.persons, &self.rentals, &self.reservations, person_id, car_id) {
+            let reservation = Reservation {
+                person_id: person_id.to_string(), // CHANGED: speichern als String
+                car_id: car_id.to_string(),       // CHANGED
+                priority: self.plan_priority(person_id),
+                created_day: self.current_day,
+                start_at: window.map(|(start, _)| start),
+                end_at: window.map(|(_, end)| end),
+            };
+            self.open_record(ReservationRecord::for_car(&reservation));
+            self.reservations.push(reservation);
+            self.reservations_created += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Gemeinsamer Teil von reserve_class und reserve_class_between
+    fn reserve_class_in(&mut self, person_id: &str, filter: CarFilter, window: Option<(u64, u64)>) -> bool {
+        let person = match find_index_persons(&self.persons, person_id) {
+            Some(index) => &self.persons[index],
+            None => return false,
+        };
+        if !self.within_reservation_quota(person_id)
+            || !p_can_reserve_class(&self.persons, &self.rentals, &self.class_reservations, person_id, &filter)
+        {
+            return false;
+        }
+        // Nur Wünsche, die mindestens ein Auto der Flotte erfüllen kann, das die Person fahren darf und der Tarif enthält
+        let possible = self.cars.iter().any(|c| {
+            c.status != CarStatus::Retired
+                && filter.matches(c)
+                && may_drive(person, c, &self.policy, self.current_day)
+                && plan_allows(&self.plans, person, c)
+        });
+        if !possible {
+            return false;
+        }
+
+        // Nummern werden nie wiederverwendet, damit der Verlauf eindeutig bleibt
+        let id = self.class_reservations
+            .iter()
+            .map(|r| r.id)
+            .chain(self.reservation_history.iter().filter_map(|r| r.class_reservation_id))
+            .max()
+            .map_or(1, |id| id + 1);
+        let reservation = ClassReservation {
+            id,
+            person_id: person_id.to_string(),
+            filter,
+            priority: self.plan_priority(person_id),
+            created_day: self.current_day,
+            start_at: window.map(|(start, _)| start),
+            end_at: window.map(|(_, end)| end),
+        };
+        self.open_record(ReservationRecord::for_class(&reservation));
+        self.class_reservations.push(reservation);
+        self.reservations_created += 1;
+        true
+    }
+
+    // Frühester Beginn eines offenen Zeitfensters nach jetzt, höchstens bis until
+    fn next_window_start(&self, until: u64) -> Option<u64> {
+        let now = self.now();
+        self.reservations
+            .iter()
+            .filter_map(|r| r.start_at)
+            .chain(self.class_reservations.iter().filter_map(|r| r.start_at))
+            .filter(|start| *start > now && *start <= until)
+            .min()
+    }
+
+    // Zeitfenster einer Reservierung: nicht leer und nicht schon vorbei
+    fn valid_window(&self, start_at: u64, end_at: u64) -> bool {
+        start_at < end_at && end_at > self.now()
+    }
+
+    // Gebühr verbuchen und der Person die Rechnung schicken; Beträge von 0 entfallen
+    fn bill(&mut self, person_id: &str, car_id: &str, kind: ChargeKind, amount_cents: u32, what: String) {
+        if amount_cents == 0 {
+            return;
+        }
+        self.notify(person_id, NotificationKind::Invoice, format!("Rechnung: {}, {:.2} €", what, amount_cents as f32 / 100.0));
+        // Gebühren werden nur angehängt, die letzte trägt also die höchste Nummer
+        self.charges.push(Charge {
+            id: self.charges.last().map_or(1, |c| c.id + 1),
+            person_id: person_id.to_string(),
+            car_id: car_id.to_string(),
+            day: self.current_day,
+            kind,
+            amount_cents,
+        });
+    }
+
+    // Monatsgebühr am Verlängerungstag; ein Tarif, den es nicht mehr gibt, endet
+    fn renew_subscriptions(&mut self) {
+        let mut due: Vec<(String, String, u32)> = Vec::new();
+        for p in self.persons.iter_mut() {
+            let renewal_day = match &p.subscription {
+                Some(s) => s.renewal_day,
+                None => continue,
+            };
+            if renewal_day > self.current_day {
+                continue;
+            }
+            let plan = p.subscription.as_ref().and_then(|s| self.plans.iter().find(|plan| plan.name == s.plan));
+            match plan {
+                Some(plan) => {
+                    due.push((p.identifier.clone(), plan.name.clone(), plan.monthly_fee_cents));
+                    if let Some(s) = p.subscription.as_mut() {
+                        s.renewal_day += DAYS_PER_MONTH;
+                    }
+                }
+                None => p.subscription = None,
+            }
+        }
+        for (person_id, plan_name, fee) in due {
+            self.bill(&person_id, "", ChargeKind::MembershipFee, fee, format!("Monatsgebühr Tarif '{}'", plan_name));
+        }
+    }
+
+    // Priorität aus dem Tarif der Person
+    fn plan_priority(&self, person_id: &str) -> u32 {
+        find_index_persons(&self.persons, person_id)
+            .and_then(|index| person_plan(&self.plans, &self.persons[index]))
+            .map_or(BASIC_PRIORITY, |plan| plan.priority)
+    }
+
+    /// Setzt die Priorität offener Reservierungen beim Übernehmen eines fremden Stands (z.B. vom Frontend).
+    /// Reservierungen, die es in `stored` schon gibt, behalten ihre festgeschriebene Priorität,
+    /// neue bekommen die ihres Tarifs; eine mitgeschickte Priorität zählt nicht.
+    pub fn enforce_priorities(&mut self, stored: &CarSharing) {
+        for index in 0..self.reservations.len() {
+            let r = &self.reservations[index];
+            let priority = match find_index_reservations(&stored.reservations, &r.person_id, &r.car_id) {
+                Some(s) => stored.reservations[s].priority,
+                None => self.plan_priority(&r.person_id),
+            };
+            self.reservations[index].priority = priority;
+            self.sync_record_priority(ReservationRecord::for_car(&self.reservations[index]));
+        }
+        for index in 0..self.class_reservations.len() {
+            let r = &self.class_reservations[index];
+            let priority = match stored.class_reservations.iter().find(|s| s.id == r.id && s.person_id == r.person_id) {
+                Some(s) => s.priority,
+                None => self.plan_priority(&r.person_id),
+            };
+            self.class_reservations[index].priority = priority;
+            self.sync_record_priority(ReservationRecord::for_class(&self.class_reservations[index]));
+        }
+    }
+
+    fn sync_record_priority(&mut self, template: ReservationRecord) {
+        if let Some(record) = self.reservation_history.iter_mut().find(|r| r.is_open_for(&template)) {
+            record.priority = template.priority;
+        }
+    }
+
+    // Fahrzeugklasse im Tarif der Person (unbekannte Personen oder Autos scheitern an anderer Stelle)
+    fn plan_permits(&self, person_id: &str, car_id: &str) -> bool {
+        match (find_index_persons(&self.persons, person_id), find_index_cars(&self.cars, car_id)) {
+            (Some(p), Some(c)) => plan_allows(&self.plans, &self.persons[p], &self.cars[c]),
+            _ => true,
+        }
+    }
+
+    // Grenzen für neue Reservierungen: offene je Tarif und Policy, neue je Tag, Sperrfrist nach No-Show
+    fn within_reservation_quota(&self, person_id: &str) -> bool {
+        let person = match find_index_persons(&self.persons, person_id) {
+            Some(index) => &self.persons[index],
+            None => return true,
+        };
+        if person.reservation_cooldown_until.is_some_and(|until| self.current_day < until) {
+            return false;
+        }
+
+        let open = (self.reservations.iter().filter(|r| r.person_id == person_id).count()
+            + self.class_reservations.iter().filter(|r| r.person_id == person_id).count()) as u32;
+        for limit in [person_plan(&self.plans, person).map(|plan| plan.max_reservations), self.policy.max_open_reservations] {
+            if limit.is_some_and(|limit| open >= limit) {
+                return false;
+            }
+        }
+
+        // Auch stornierte Reservierungen zählen, sonst ließe sich die Grenze umgehen
+        let today = self
+            .reservation_history
+            .iter()
+            .filter(|r| r.person_id == person_id && r.created_day == self.current_day)
+            .count() as u32;
+        self.policy.max_reservations_per_day.is_none_or(|limit| today < limit)
+    }
+
+    // Erfüllte Reservierung, aus der die laufende Ausleihe des Autos stammt
+    fn reserved_rental(&self, person_id: &str, car_id: &str) -> Option<usize> {
+        self.reservation_history.iter().rposition(|r| {
+            r.person_id == person_id
+                && r.state == ReservationState::Fulfilled
+                && r.assigned_car_id.as_deref() == Some(car_id)
+                && r.rental_open
+        })
+    }
+
+    // Erfüllte Reservierung, deren übergebenes Auto noch abgeholt werden muss
+    fn awaiting_pickup(&self, person_id: &str, car_id: &str) -> Option<usize> {
+        self.reserved_rental(person_id, car_id).filter(|&index| self.reservation_history[index].pickup_due_day.is_some())
+    }
+
+    // Autos, die bis zum Ende der Abholfrist nicht abgeholt wurden, werden wieder frei; das gilt als No-Show
+    fn release_unclaimed_cars(&mut self) {
+        let overdue: Vec<usize> = (0..self.reservation_history.len())
+            .filter(|&index| {
+                let r = &self.reservation_history[index];
+                r.state == ReservationState::Fulfilled && r.rental_open && r.pickup_due_day.is_some_and(|day| day < self.current_day)
+            })
+            .collect();
+
+        for index in overdue {
+            self.reservation_history[index].pickup_due_day = None;
+            self.reservation_history[index].rental_open = false;
+            let person_id = self.reservation_history[index].person_id.clone();
+            let car_id = match self.reservation_history[index].assigned_car_id.clone() {
+                Some(car_id) => car_id,
+                None => continue,
+            };
+            let (index_rental, index_car) = match (find_index_rentals(&self.rentals, &person_id, &car_id), find_index_cars(&self.cars, &car_id)) {
+                (Some(r), Some(c)) => (r, c),
+                _ => continue,
+            };
+            self.rentals.remove(index_rental);
+            self.cars[index_car].status = CarStatus::Available;
+            self.cars[index_car].rented_day = None;
+            self.cars[index_car].rented_at = None;
+            self.record_no_show(index, "Auto nicht abgeholt");
+        }
+    }
+
+    // Die erfüllte Reservierung im Verlauf wird zum No-Show, die Person bekommt ggf. eine Sperrfrist
+    fn record_no_show(&mut self, record_index: usize, reason: &str) {
+        let record = &mut self.reservation_history[record_index];
+        let person_id = record.person_id.clone();
+        let car_id = record.assigned_car_id.clone().unwrap_or_default();
+        record.state = ReservationState::NoShow;
+        record.transitions.push(ReservationTransition { day: self.current_day, state: ReservationState::NoShow, reason: reason.to_string() });
+
+        let cooldown = self.policy.no_show_cooldown_days;
+        let until = self.current_day + cooldown;
+        if let Some(index) = find_index_persons(&self.persons, &person_id) {
+            self.persons[index].no_shows += 1;
+            if cooldown > 0 {
+                self.persons[index].reservation_cooldown_until = Some(until);
+            }
+        }
+        let message = if cooldown > 0 {
+            format!("Sie haben Auto '{}' nicht genutzt. Neue Reservierungen sind erst ab Tag {} möglich.", car_id, until)
+        } else {
+            format!("Sie haben Auto '{}' nicht genutzt.", car_id)
+        };
+        self.notify(&person_id, NotificationKind::NoShow, message);
+    }
+
+    fn notify(&mut self, person_id: &str, kind: NotificationKind, message: String) {
+        self.notifications.push(Notification {
+            id: self.notifications.iter().map(|n| n.id).max().map_or(1, |id| id + 1),
+            day: self.current_day,
+            person_id: person_id.to_string(),
+            kind,
+            message,
+        });
+    }
+
+    // Neue Reservierung im Verlauf anlegen
+    fn open_record(&mut self, mut record: ReservationRecord) -> usize {
+        record.id = self.reservation_history.iter().map(|r| r.id).max().map_or(1, |id| id + 1);
+        record.transitions.push(ReservationTransition {
+            day: record.created_day,
+            state: ReservationState::Pending,
+            reason: "Angelegt".to_string(),
+        });
+        self.reservation_history.push(record);
+        self.reservation_history.len() - 1
+    }
+
+    // Übergang einer offenen (wartenden oder ruhenden) Reservierung im Verlauf vermerken.
+    // Reservierungen aus älteren Ständen ohne Verlaufseintrag werden dabei nachgetragen.
+    fn close_record(&mut self, template: ReservationRecord, state: ReservationState, reason: String) -> usize {
+        let index = match self.reservation_history.iter().position(|r| r.is_open_for(&template)) {
+            Some(index) => index,
+            None => self.open_record(template),
+        };
+        let record = &mut self.reservation_history[index];
+        record.state = state;
+        record.transitions.push(ReservationTransition { day: self.current_day, state, reason });
+        index
+    }
+
+    // Die Reservierung, über die die Person das Auto bekommen hat, ist erfüllt,
+    // alle anderen Reservierungen der Person sind damit hinfällig
+    fn close_fulfilled(&mut self, person_id: &str, car_id: &str) {
+        let pickup_due_day = self.policy.pickup_deadline_days.map(|days| self.current_day + days);
+        let specific = find_index_reservations(&self.reservations, person_id, car_id).is_some();
+        let fulfilled_class = if specific {
+            None
+        } else {
+            let car = find_index_cars(&self.cars, car_id).map(|index| self.cars[index].clone());
+            self.class_reservations
+                .iter()
+                .filter(|r| r.person_id == person_id && car.as_ref().is_some_and(|c| r.filter.matches(c)))
+                .max_by_key(|r| r.priority)
+                .map(|r| r.id)
+        };
+
+        let superseded = format!("Person hat bereits Auto '{}' erhalten", car_id);
+        for r in self.reservations.clone().iter().filter(|r| r.person_id == person_id) {
+            if r.car_id == car_id {
+                let index = self.close_record(ReservationRecord::for_car(r), ReservationState::Fulfilled, "Auto übergeben".to_string());
+                self.reservation_history[index].assigned_car_id = Some(car_id.to_string());
+                self.reservation_history[index].pickup_due_day = pickup_due_day;
+                self.reservation_history[index].rental_open = true;
+                self.notify(person_id, NotificationKind::ReservationFulfilled, format!("Ihre Reservierung ist erfüllt, Auto '{}' ist für Sie gebucht.", car_id));
+            } else {
+                self.close_record(ReservationRecord::for_car(r), ReservationState::Superseded, superseded.clone());
+            }
+        }
+        for r in self.class_reservations.clone().iter().filter(|r| r.person_id == person_id) {
+            if Some(r.id) == fulfilled_class {
+                let index = self.close_record(ReservationRecord::for_class(r), ReservationState::Fulfilled, format!("Auto '{}' übergeben", car_id));
+                self.reservation_history[index].assigned_car_id = Some(car_id.to_string());
+                self.reservation_history[index].pickup_due_day = pickup_due_day;
+                self.reservation_history[index].rental_open = true;
+                self.notify(person_id, NotificationKind::ReservationFulfilled, format!("Ihre Reservierung ist erfüllt, Auto '{}' ist für Sie gebucht.", car_id));
+            } else {
+                self.close_record(ReservationRecord::for_class(r), ReservationState::Superseded, superseded.clone());
+            }
+        }
+    }
+
+    // Stand einer offenen Reservierung im Verlauf (None = noch kein Eintrag)
+    fn record_state(&self, template: &ReservationRecord) -> Option<ReservationState> {
+        self.reservation_history.iter().find(|r| r.is_open_for(template)).map(|r| r.state)
+    }
+
+    fn push_reservation_event(&mut self, record_index: usize, kind: ReservationEventKind, reason: String) {
+        let record = &self.reservation_history[record_index];
+        self.reservation_events.push(ReservationEvent {
+            id: self.reservation_events.iter().map(|e| e.id).max().map_or(1, |id| id + 1),
+            day: self.current_day,
+            record_id: record.id,
+            person_id: record.person_id.clone(),
+            kind,
+            reason,
+        });
+    }
+
+    // Vergleichbares Auto für eine Reservierung, deren Auto ausgemustert wurde:
+    // gleiche Klasse, Getriebe und Antrieb, mindestens so viele Sitze; verfügbare Autos zuerst
+    fn comparable_car(&self, person_id: &str, retired_car_id: &str) -> Option<String> {
+        let retired = &self.cars[find_index_cars(&self.cars, retired_car_id)?];
+        let filter = CarFilter {
+            class: Some(retired.class),
+            min_seats: Some(retired.seats),
+            transmission: Some(retired.transmission),
+            powertrain: Some(retired.powertrain),
+            features: Vec::new(),
+            station_id: None,
+        };
+        self.cars
+            .iter()
+            .filter(|c| c.identifier != retired_car_id && c.status != CarStatus::Retired && filter.matches(c))
+            .filter(|c| find_index_reservations(&self.reservations, person_id, &c.identifier).is_none())
+            .min_by_key(|c| (c.status != CarStatus::Available, filter.surplus(c), c.identifier.clone()))
+            .map(|c| c.identifier.clone())
+    }
+
+    // Wendet die Folgeregeln der FleetPolicy auf offene Reservierungen an:
+    // gesperrte Personen, ausgemusterte Autos, keine passenden Autos mehr und Verfall
+    fn sweep_reservations(&mut self) {
+        let mut actions: Vec<(ReservationRecord, SweepAction)> = Vec::new();
+
+        for r in self.reservations.iter() {
+            let record = ReservationRecord::for_car(r);
+            let car_retired = match find_index_cars(&self.cars, &r.car_id) {
+                Some(index) => self.cars[index].status == CarStatus::Retired,
+                None => true,
+            };
+            let action = match self.person_action(&record) {
+                Some(action) => action,
+                None if car_retired => match self.policy.on_car_retired {
+                    RetiredCarRule::Cancel => SweepAction::Cancel("Auto ausgemustert".to_string()),
+                    RetiredCarRule::Reassign => match self.comparable_car(&r.person_id, &r.car_id) {
+                        Some(car_id) => SweepAction::Reassign(car_id),
+                        None => SweepAction::Cancel("Auto ausgemustert, kein vergleichbares Auto".to_string()),
+                    },
+                },
+                None => continue,
+            };
+            actions.push((record, action));
+        }
+        for r in self.class_reservations.iter() {
+            let record = ReservationRecord::for_class(r);
+            let no_match = !self.cars.iter().any(|c| c.status != CarStatus::Retired && r.filter.matches(c));
+            let action = match self.person_action(&record) {
+                Some(action) => action,
+                None if no_match => SweepAction::Cancel("Kein passendes Auto mehr in der Flotte".to_string()),
+                None => continue,
+            };
+            actions.push((record, action));
+        }
+
+        for (record, action) in actions {
+            match action {
+                SweepAction::Cancel(reason) => {
+                    self.remove_from_queue(&record);
+                    let index = self.close_record(record, ReservationState::Rejected, reason.clone());
+                    self.push_reservation_event(index, ReservationEventKind::Cancelled, reason);
+                }
+                SweepAction::Expire(reason) => {
+                    self.remove_from_queue(&record);
+                    let message = match &record.car_id {
+                        Some(car_id) => format!("Ihre Reservierung für Auto '{}' ist verfallen: {}.", car_id, reason),
+                        None => format!("Ihre Reservierung nach Merkmalen ist verfallen: {}.", reason),
+                    };
+                    let person_id = record.person_id.clone();
+                    self.close_record(record, ReservationState::Expired, reason);
+                    self.notify(&person_id, NotificationKind::ReservationExpired, message);
+                }
+                SweepAction::Suspend(reason) => {
+                    let index = self.close_record(record, ReservationState::Suspended, reason.clone());
+                    self.push_reservation_event(index, ReservationEventKind::Suspended, reason);
+                }
+                // Zwei Reservierungen der Person können auf dasselbe Auto fallen
+                SweepAction::Reassign(car_id) if find_index_reservations(&self.reservations, &record.person_id, &car_id).is_some() => {
+                    self.remove_from_queue(&record);
+                    let reason = "Auto ausgemustert, vergleichbares Auto bereits reserviert".to_string();
+                    let index = self.close_record(record, ReservationState::Rejected, reason.clone());
+                    self.push_reservation_event(index, ReservationEventKind::Cancelled, reason);
+                }
+                SweepAction::Reassign(car_id) => {
+                    let old_car_id = record.car_id.clone().unwrap_or_default();
+                    let reason = format!("Auto '{}' ausgemustert, umgebucht auf '{}'", old_car_id, car_id);
+                    if let Some(index) = find_index_reservations(&self.reservations, &record.person_id, &old_car_id) {
+                        self.reservations[index].car_id = car_id.clone();
+                    }
+                    let index = self.close_record(record, ReservationState::Pending, reason.clone());
+                    self.reservation_history[index].car_id = Some(car_id);
+                    self.push_reservation_event(index, ReservationEventKind::Reassigned, reason);
+                }
+            }
+        }
+    }
+
+    // Folgeregel für die Person: Sperre (je nach Policy stornieren oder ruhen lassen) und Verfall
+    fn person_action(&self, record: &ReservationRecord) -> Option<SweepAction> {
+        let blocked = self.persons.iter().any(|p| p.identifier == record.person_id && p.status.is_blocked());
+        if blocked {
+            return match self.policy.on_person_blocked {
+                BlockedPersonRule::Cancel => Some(SweepAction::Cancel("Person gesperrt".to_string())),
+                BlockedPersonRule::Suspend if self.record_state(record) != Some(ReservationState::Suspended) => {
+                    Some(SweepAction::Suspend("Person gesperrt, ruht bis zur Aufhebung der Sperre".to_string()))
+                }
+                BlockedPersonRule::Suspend => None,
+            };
+        }
+        // Mit Zeitfenster gilt dessen Ende statt der Verfallsfrist
+        match record.end_at {
+            Some(end_at) if end_at <= self.now() => return Some(SweepAction::Expire("Zeitfenster abgelaufen".to_string())),
+            Some(_) => return None,
+            None => {}
+        }
+        match self.policy.reservation_expiry_days {
+            Some(days) if self.current_day.saturating_sub(record.created_day) > days => {
+                Some(SweepAction::Expire(format!("Nach {} Tagen nicht erfüllt", days)))
+            }
+            _ => None,
+        }
+    }
+
+    fn remove_from_queue(&mut self, record: &ReservationRecord) {
+        self.reservations.retain(|r| !(record.car_id.as_ref() == Some(&r.car_id) && r.person_id == record.person_id));
+        self.class_reservations.retain(|r| record.class_reservation_id != Some(r.id));
+    }
+
+    // Ruhende Reservierungen der Person wieder aktivieren (nach Verlängerung des Führerscheins)
+    fn resume_reservations(&mut self, person_id: &str, reason: &str) {
+        let mut records: Vec<ReservationRecord> = Vec::new();
+        records.extend(self.reservations.iter().filter(|r| r.person_id == person_id).map(ReservationRecord::for_car));
+        records.extend(self.class_reservations.iter().filter(|r| r.person_id == person_id).map(ReservationRecord::for_class));
+
+        for record in records {
+            if self.record_state(&record) == Some(ReservationState::Suspended) {
+                let reason = reason.to_string();
+                let index = self.close_record(record, ReservationState::Pending, reason.clone());
+                self.push_reservation_event(index, ReservationEventKind::Resumed, reason);
+            }
+        }
+    }
+
     // CHANGED: convenience constructor für UI
     pub fn new() -> Self {
         Self {
@@ -545,6 +2726,322 @@ impl CarSharing {
             rentals: vec![],
             reservations: vec![],
             current_day: 0,
+            minute_of_day: 0,
+            reservations_created: 0,
+            reservations_fulfilled: 0,
+            history: vec![],
+            scheduled_services: vec![],
+            policy: FleetPolicy::default(),
+            service_records: vec![],
+            damages: vec![],
+            charges: vec![],
+            stations: vec![],
+            class_reservations: vec![],
+            reservation_history: vec![],
+            reservation_events: vec![],
+            notifications: vec![],
+            plans: vec![],
+            version: 0,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_person(status: &str) -> Result<Person, serde_json::Error> {
+        serde_json::from_str(&format!(r#"{{"identifier": "p1", "license_valid_days": 0, "status": {}}}"#, status))
+    }
+
+    #[test]
+    fn legacy_person_status_values_still_load() {
+        let status = |json: &str| stored_person(json).map(|p| p.status).ok();
+        assert_eq!(status(r#""Active""#), Some(PersonStatus::Active));
+        // Vor den Sperrgründen gab es nur "Blocked"
+        assert_eq!(status(r#""Blocked""#), Some(PersonStatus::Blocked(BlockReason::LicenseExpired)));
+        assert_eq!(status(r#"{"Blocked": "Damage"}"#), Some(PersonStatus::Blocked(BlockReason::Damage)));
+    }
+
+    #[test]
+    fn unknown_person_status_is_an_error() {
+        let error = stored_person(r#""Suspended""#).err().expect("unbekannter Status darf nicht laden");
+        assert!(error.to_string().contains("Unbekannter Personenstatus 'Suspended'"));
+        assert!(stored_person(r#"{"Blocked": "Vacation"}"#).is_err());
+    }
+
+    #[test]
+    fn stored_blocks_survive_or_expire_after_one_day() {
+        let mut cs: CarSharing = serde_json::from_str(
+            r#"{
+                "persons": [
+                    {"identifier": "alt", "license_valid_days": 0, "status": "Blocked"},
+                    {"identifier": "schaden", "license_valid_days": 100, "status": {"Blocked": "Damage"}},
+                    {"identifier": "pause", "license_valid_days": 100, "status": {"Blocked": "ManualSuspension"},
+                     "blocks": [{"reason": "ManualSuspension", "since_day": 0, "until_day": 3}]}
+                ],
+                "cars": [], "rentals": [], "reservations": [], "current_day": 2
+            }"#,
+        )
+        .unwrap();
+        cs.run_day();
+
+        // Sperren ohne Sperrliste gelten unbefristet weiter und werden übernommen
+        let person = |id: &str| cs.persons.iter().find(|p| p.identifier == id).unwrap().clone();
+        assert_eq!(person("alt").status, PersonStatus::Blocked(BlockReason::LicenseExpired));
+        assert_eq!(person("alt").blocks.len(), 1);
+        assert_eq!(person("schaden").status, PersonStatus::Blocked(BlockReason::Damage));
+        assert_eq!(person("schaden").blocks.len(), 1);
+        // Die befristete Sperre ist an Tag 3 abgelaufen
+        assert_eq!(person("pause").status, PersonStatus::Active);
+        assert!(person("pause").blocks.is_empty());
+
+        assert!(cs.renew_license("alt", 100));
+        assert_eq!(cs.get_person_status("alt"), Some(PersonStatus::Active));
+    }
+
+    fn fleet(persons: &[&str], cars: &[&str]) -> CarSharing {
+        let mut cs = CarSharing::new();
+        for id in persons {
+            cs.register_person(Person { license_valid_days: 1000, ..Person::new(id) });
+        }
+        for id in cars {
+            cs.register_car(Car::new(id));
+        }
+        cs
+    }
+
+    fn no_show_state(cs: &CarSharing, person_id: &str) -> (u32, Option<u32>, Vec<ReservationState>) {
+        let person = cs.persons.iter().find(|p| p.identifier == person_id).unwrap();
+        let states = cs.reservation_history.iter().filter(|r| r.person_id == person_id).map(|r| r.state).collect();
+        (person.no_shows, person.reservation_cooldown_until, states)
+    }
+
+    #[test]
+    fn unused_reserved_car_is_a_no_show() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        cs.policy.no_show_cooldown_days = 3;
+        assert!(cs.reserve_car("p1", "c1"));
+        cs.process_reservations();
+        assert!(cs.return_car("p1", "c1", 0, ReturnDetails::default()));
+        assert_eq!(no_show_state(&cs, "p1"), (1, Some(3), vec![ReservationState::NoShow]));
+    }
+
+    #[test]
+    fn unused_direct_rental_after_a_used_reservation_is_no_no_show() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        cs.policy.no_show_cooldown_days = 3;
+        assert!(cs.reserve_car("p1", "c1"));
+        cs.process_reservations();
+        assert!(cs.return_car("p1", "c1", 40, ReturnDetails::default()));
+
+        // Am selben Tag direkt wieder gemietet und ungenutzt zurückgegeben: die alte Reservierung bleibt erfüllt
+        assert!(cs.rent_car("p1", "c1"));
+        assert!(cs.return_car("p1", "c1", 0, ReturnDetails::default()));
+        assert_eq!(no_show_state(&cs, "p1"), (0, None, vec![ReservationState::Fulfilled]));
+    }
+
+    #[test]
+    fn car_not_picked_up_by_the_deadline_is_released() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        cs.policy.pickup_deadline_days = Some(1);
+        assert!(cs.reserve_car("p1", "c1"));
+        cs.process_reservations();
+        assert_eq!(cs.pickup_due_day("p1", "c1"), Some(1));
+
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Rented));
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Available));
+        assert!(cs.rentals.is_empty());
+        assert_eq!(no_show_state(&cs, "p1"), (1, None, vec![ReservationState::NoShow]));
+    }
+
+    #[test]
+    fn simulating_zero_days_changes_nothing() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        assert!(cs.reserve_car("p1", "c1"));
+        let before = cs.clone();
+        cs.simulate_n_days(0);
+        cs.advance_minutes(0);
+        assert!(cs == before);
+    }
+
+    #[test]
+    fn each_simulated_day_allocates_in_its_day_change() {
+        let mut cs = fleet(&["p1", "p2"], &["c1", "c2"]);
+        assert!(cs.reserve_car("p1", "c1"));
+        assert!(cs.reserve_car("p2", "c2"));
+        cs.simulate_n_days(2);
+
+        // Die Zuteilung des Tageswechsels steht vollständig im Tageswert von Tag 1
+        let fulfilled: Vec<(u32, u32)> = cs.history.iter().map(|s| (s.day, s.reservations_fulfilled)).collect();
+        assert_eq!(fulfilled, vec![(1, 2), (2, 0)]);
+        assert_eq!(cs.reservations_fulfilled, 2);
+        for record in cs.reservation_history.iter() {
+            let days: Vec<u32> = record.transitions.iter().filter(|t| t.state == ReservationState::Fulfilled).map(|t| t.day).collect();
+            assert_eq!(days, vec![1]);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn window_inside_a_skipped_day_is_served_at_its_start() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        let start = MINUTES_PER_DAY + 10 * MINUTES_PER_HOUR;
+        assert!(cs.reserve_car_between("p1", "c1", start, start + 2 * MINUTES_PER_HOUR));
+        cs.simulate_n_days(3);
+
+        assert_eq!(cs.current_day, 3);
+        assert_eq!(cs.rentals, vec![("p1".to_string(), "c1".to_string())]);
+        assert_eq!(cs.cars[0].rented_at, Some(start));
+        assert_eq!(cs.reservation_history[0].state, ReservationState::Fulfilled);
+    }
+
+    #[test]
+    fn huge_prices_are_capped_instead_of_overflowing() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        cs.register_plan(MembershipPlan {
+            name: "Teuer".to_string(),
+            monthly_fee_cents: 0,
+            per_km_cents: u32::MAX,
+            per_hour_cents: Some(u32::MAX),
+            priority: 0,
+            max_reservations: 1,
+            allowed_classes: vec![],
+        });
+        cs.subscribe_person("p1", "Teuer");
+        assert!(cs.rent_car("p1", "c1"));
+        cs.advance_minutes(3 * MINUTES_PER_HOUR);
+        assert!(cs.return_car("p1", "c1", 2, ReturnDetails::default()));
+
+        let amounts: Vec<(ChargeKind, u32)> = cs.charges.iter().map(|c| (c.kind, c.amount_cents)).collect();
+        assert_eq!(amounts, vec![(ChargeKind::Mileage, u32::MAX), (ChargeKind::RentalTime, u32::MAX)]);
+    }
+
+    #[test]
+    fn extend_maintenance_rejects_overflow() {
+        let mut cs = fleet(&[], &["c1"]);
+        assert!(cs.start_maintenance("c1", ServiceKind::Maintenance, 2));
+        assert!(!cs.extend_maintenance("c1", u32::MAX));
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Maintenance(2)));
+        assert!(cs.extend_maintenance("c1", 3));
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Maintenance(5)));
+    }
+
+    #[test]
+    fn due_maintenance_waits_for_a_full_workshop() {
+        let mut cs = fleet(&["p1"], &["c1", "c2"]);
+        cs.policy.workshop_capacity = Some(1);
+        cs.cars[0].mileage = MAINTENANCE_KM - 10;
+        assert!(cs.start_maintenance("c2", ServiceKind::Tuv, 1));
+        assert!(cs.rent_car("p1", "c1"));
+        assert!(cs.return_car("p1", "c1", 20, ReturnDetails::default()));
+
+        // Die Werkstatt ist voll: c1 bleibt verfügbar und bekommt einen Termin
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Available));
+        let queued: Vec<(&str, ServiceKind)> = cs.scheduled_services.iter().map(|s| (s.car_id.as_str(), s.kind)).collect();
+        assert_eq!(queued, vec![("c1", ServiceKind::Maintenance)]);
+
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Maintenance(MAINTENANCE_DAYS)));
+        assert!(cs.scheduled_services.is_empty());
+    }
+
+    #[test]
+    fn due_maintenance_is_queued_behind_a_damage_repair() {
+        let mut cs = fleet(&["p1"], &["c1"]);
+        cs.cars[0].mileage = MAINTENANCE_KM - 10;
+        assert!(cs.rent_car("p1", "c1"));
+        let damage = DamageReport {
+            severity: DamageSeverity::Major,
+            description: "Delle".to_string(),
+            location: "Tür".to_string(),
+            repair_days: Some(2),
+        };
+        assert!(cs.return_car("p1", "c1", 20, ReturnDetails { damage: Some(damage), ..ReturnDetails::default() }));
+
+        // Erst die Reparatur, die fällige Wartung wartet als Termin
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Repair(2)));
+        assert_eq!(cs.scheduled_services.len(), 1);
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Repair(1)));
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Maintenance(MAINTENANCE_DAYS)));
+        assert!(cs.scheduled_services.is_empty());
+    }
+
+    #[test]
+    fn rented_car_keeps_its_home_slot() {
+        let mut cs = fleet(&["p1"], &[]);
+        cs.register_station(Station {
+            identifier: "s1".to_string(),
+            name: "Bahnhof".to_string(),
+            latitude: 52.52,
+            longitude: 13.40,
+            capacity: 2,
+        });
+        assert!(cs.register_car(Car { station_id: Some("s1".to_string()), ..Car::new("c1") }));
+        assert!(cs.rent_car("p1", "c1"));
+        assert_eq!(cs.get_station_occupancy("s1"), Some(1));
+
+        // Der Platz des vermieteten Autos bleibt belegt, die Station ist mit einem zweiten Auto voll
+        assert!(cs.register_car(Car { station_id: Some("s1".to_string()), ..Car::new("c2") }));
+        assert_eq!(cs.get_station_occupancy("s1"), Some(2));
+        assert!(!cs.register_car(Car { station_id: Some("s1".to_string()), ..Car::new("c3") }));
+
+        // Die Rückgabe an die Heimatstation klappt trotzdem
+        assert!(cs.return_car("p1", "c1", 10, ReturnDetails { station_id: Some("s1".to_string()), ..ReturnDetails::default() }));
+        assert_eq!(cs.get_station_occupancy("s1"), Some(2));
+    }
+
+    // c1 wird beim nächsten Tageswechsel ausgemustert, c2 ist vergleichbar, aber an p2 vermietet
+    fn fleet_with_retiring_car(rule: RetiredCarRule) -> CarSharing {
+        let mut cs = fleet(&["p1", "p2"], &["c1", "c2"]);
+        cs.policy.on_car_retired = rule;
+        cs.cars[0].age_days = MAX_AGE_DAYS - 1;
+        assert!(cs.rent_car("p2", "c2"));
+        assert!(cs.reserve_car("p1", "c1"));
+        cs.run_day();
+        assert_eq!(cs.get_car_status("c1"), Some(CarStatus::Retired));
+        cs
+    }
+
+    #[test]
+    fn retired_car_cascades_to_its_reservations() {
+        let cs = fleet_with_retiring_car(RetiredCarRule::Cancel);
+        assert!(cs.reservations.is_empty());
+        assert_eq!(cs.reservation_history[0].state, ReservationState::Rejected);
+        let events: Vec<ReservationEventKind> = cs.reservation_events.iter().map(|e| e.kind).collect();
+        assert_eq!(events, vec![ReservationEventKind::Cancelled]);
+
+        let cs = fleet_with_retiring_car(RetiredCarRule::Reassign);
+        let queued: Vec<(&str, &str)> = cs.reservations.iter().map(|r| (r.person_id.as_str(), r.car_id.as_str())).collect();
+        assert_eq!(queued, vec![("p1", "c2")]);
+        assert_eq!(cs.reservation_history[0].state, ReservationState::Pending);
+        assert_eq!(cs.reservation_history[0].car_id.as_deref(), Some("c2"));
+        let events: Vec<ReservationEventKind> = cs.reservation_events.iter().map(|e| e.kind).collect();
+        assert_eq!(events, vec![ReservationEventKind::Reassigned]);
+    }
+
+    #[test]
+    fn grace_period_expires_into_a_license_block() {
+        let mut cs = fleet(&["p1"], &[]);
+        cs.policy.license_grace_days = 2;
+        cs.persons[0].license_valid_days = 1;
+
+        // Ablauftag: Schonfrist beginnt, noch keine Sperre
+        cs.run_day();
+        assert_eq!(cs.persons[0].grace_days_left, Some(2));
+        assert!(!cs.persons[0].status.is_blocked());
+        cs.run_day();
+        assert_eq!(cs.persons[0].grace_days_left, Some(1));
+        assert!(!cs.persons[0].status.is_blocked());
+
+        // Schonfrist aufgebraucht: Sperre wegen des Führerscheins
+        cs.run_day();
+        assert_eq!(cs.persons[0].grace_days_left, None);
+        assert!(cs.persons[0].has_block(BlockReason::LicenseExpired));
+
+        assert!(cs.renew_license("p1", 30));
+        assert!(!cs.persons[0].status.is_blocked());
+    }
+}
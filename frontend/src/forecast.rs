@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::carsharing::{
+    retirement_score, Car, CarSharing, CarStatus, MAINTENANCE_KM, MAX_AGE_DAYS, MAX_KM, MAX_RENTALS, TUV_KM,
+};
+use crate::statistics::km_until_next;
+
+// Ab wie vielen Resttagen ein Auto in der "bald fällig"-Liste erscheint
+pub const DUE_SOON_DAYS: u32 = 7;
+
+/// Prognose für die nächste Wartung, den nächsten TÜV und die Ausmusterung eines Autos.
+/// Die Tage werden aus der bisherigen Nutzung seit der Registrierung hochgerechnet
+/// und sind `None`, solange das Auto noch nie gefahren wurde.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MaintenanceForecast {
+    pub car_id: String,
+    pub avg_km_per_rental: f32,
+    pub rentals_per_day: f32,
+    pub km_per_day: f32,
+    pub km_until_maintenance: u32,
+    pub days_until_maintenance: Option<u32>,
+    pub km_until_tuv: u32,
+    pub days_until_tuv: Option<u32>,
+    // km bis score > 1.0, wenn nur noch gefahren würde (obere Schranke)
+    pub km_until_retirement: u32,
+    pub days_until_retirement: u32,
+}
+
+impl MaintenanceForecast {
+    /// Frühester Termin von Wartung oder TÜV
+    pub fn days_until_next_service(&self) -> Option<u32> {
+        match (self.days_until_maintenance, self.days_until_tuv) {
+            (Some(m), Some(t)) => Some(m.min(t)),
+            (m, t) => m.or(t),
+        }
+    }
+}
+
+/// Prognose für ein einzelnes Auto
+pub fn car_forecast(car: &Car, current_day: u32) -> MaintenanceForecast {
+    let days_in_fleet = current_day.saturating_sub(car.registered_day).max(1) as f32;
+
+    let avg_km_per_rental = if car.rental_count == 0 { 0.0 } else { car.driven_km as f32 / car.rental_count as f32 };
+    let rentals_per_day = car.rental_count as f32 / days_in_fleet;
+    let km_per_day = avg_km_per_rental * rentals_per_day;
+
+    let km_until_maintenance = km_until_next(car.mileage, MAINTENANCE_KM);
+    let km_until_tuv = km_until_next(car.mileage, TUV_KM);
+
+    // Der Score wächst täglich durch Alter, gefahrene km und Anzahl Ausleihen
+    let score = retirement_score(car);
+    let score_per_day =
+        1.0 / MAX_AGE_DAYS as f32 + km_per_day / MAX_KM as f32 + rentals_per_day / MAX_RENTALS as f32;
+    let mut days_until_retirement = if score > 1.0 {
+        0
+    } else {
+        ((1.0 - score) / score_per_day).ceil() as u32
+    };
+    days_until_retirement = days_until_retirement.min(MAX_AGE_DAYS.saturating_sub(car.age_days));
+    if rentals_per_day > 0.0 {
+        let by_rentals = (MAX_RENTALS.saturating_sub(car.rental_count) as f32 / rentals_per_day).ceil() as u32;
+        days_until_retirement = days_until_retirement.min(by_rentals);
+    }
+
+    MaintenanceForecast {
+        car_id: car.identifier.clone(),
+        avg_km_per_rental,
+        rentals_per_day,
+        km_per_day,
+        km_until_maintenance,
+        days_until_maintenance: days_for_km(km_until_maintenance, km_per_day),
+        km_until_tuv,
+        days_until_tuv: days_for_km(km_until_tuv, km_per_day),
+        km_until_retirement: ((1.0 - score).max(0.0) * MAX_KM as f32) as u32,
+        days_until_retirement,
+    }
+}
+
+/// Prognosen für alle Autos, die noch nicht ausgemustert sind
+pub fn fleet_forecast(cs: &CarSharing) -> Vec<MaintenanceForecast> {
+    cs.cars
+        .iter()
+        .filter(|c| c.status != CarStatus::Retired)
+        .map(|c| car_forecast(c, cs.current_day))
+        .collect()
+}
+
+/// Autos, deren Wartung oder TÜV voraussichtlich in höchstens `within_days` Tagen fällig ist,
+/// sortiert nach dem frühesten Termin
+pub fn due_soon(cs: &CarSharing, within_days: u32) -> Vec<MaintenanceForecast> {
+    let mut due: Vec<MaintenanceForecast> = fleet_forecast(cs)
+        .into_iter()
+        .filter(|f| f.days_until_next_service().is_some_and(|d| d <= within_days))
+        .collect();
+    due.sort_by_key(|f| f.days_until_next_service());
+    due
+}
+
+fn days_for_km(km: u32, km_per_day: f32) -> Option<u32> {
+    if km_per_day > 0.0 {
+        Some((km as f32 / km_per_day).ceil() as u32)
+    } else {
+        None
+    }
+}
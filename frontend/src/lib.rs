@@ -1 +1,5 @@
+pub mod allocation;
 pub mod carsharing;
+pub mod forecast;
+pub mod rebalancing;
+pub mod statistics;
@@ -1,10 +1,62 @@
 use yew::prelude::*;
 use gloo_net::http::Request;
+use gloo_timers::callback::Interval;
+use serde::Deserialize;
 use wasm_bindgen_futures::spawn_local;
 
+use rust_frontend::allocation::compare_strategies;
 use rust_frontend::carsharing::{
-    Car, CarSharing, CarSharingService, CarStatus, Person, PersonStatus,
+    AllocationStrategy, BlockReason, BlockedPersonRule, Car, CarClass, CarFilter, CarSharing, CarSharingService, DamageReport, DamageSeverity, LicenseClass, MembershipPlan, Person,
+    Powertrain, RetiredCarRule, ReturnDetails, ServiceKind, ServiceRecord, Station, Transmission, DAYS_PER_YEAR, DEFAULT_SEATS, FULL_ENERGY_LEVEL,
+    MINUTES_PER_DAY, MINUTES_PER_HOUR, format_time,
 };
+use rust_frontend::forecast::{due_soon, DUE_SOON_DAYS};
+use rust_frontend::rebalancing::{apply_rebalancing, plan_rebalancing};
+use rust_frontend::statistics::{expiring_licenses, fleet_statistics, DailySnapshot};
+
+// Zeitraum für die hervorgehobene Liste im Tab Persons
+const LICENSE_WARN_DAYS: u32 = 30;
+// Im Echtzeitbetrieb wird der Zustand so oft neu geladen (Millisekunden)
+const REFRESH_MS: u32 = 30_000;
+
+// Antwort von GET /api/clock
+#[derive(Clone, PartialEq, Deserialize)]
+struct ClockInfo {
+    mode: String,
+    timezone: String,
+    local_date: String,
+    next_day_at: Option<String>,
+}
+
+// Antwort von POST /api/scenarios (Auszug)
+#[derive(Clone, PartialEq, Deserialize)]
+struct ScenarioReport {
+    start_day: u32,
+    end_day: u32,
+    fleet_size: u32,
+    requests: u32,
+    fulfilled: u32,
+    unmet_demand: u32,
+    still_open: u32,
+    utilization: f64,
+    cars_retired: u32,
+    revenue_cents: u64,
+    daily: Vec<ScenarioDay>,
+}
+
+#[derive(Clone, PartialEq, Deserialize)]
+struct ScenarioDay {
+    day: u32,
+    requests: u32,
+    fulfilled: u32,
+    unmet: u32,
+    utilization: f64,
+}
+
+#[derive(Deserialize)]
+struct ItemEnvelope<T> {
+    data: T,
+}
 
 #[derive(Clone, PartialEq)]
 enum Tab {
@@ -12,7 +64,9 @@ enum Tab {
     Cars,
     Reservations,
     Rentals,
+    Stations,
     Simulation,
+    Dashboard,
 }
 
 fn tab_button(current: &Tab, tab: Tab, label: &str, on_click: Callback<MouseEvent>) -> Html {
@@ -26,6 +80,201 @@ fn tab_button(current: &Tab, tab: Tab, label: &str, on_click: Callback<MouseEven
     html! { <button style={style} onclick={on_click}>{label}</button> }
 }
 
+fn fmt_days(days: Option<u32>) -> String {
+    match days {
+        Some(d) => format!("{} Tage", d),
+        None => "unbekannt".to_string(),
+    }
+}
+
+fn fmt_window(start_at: Option<u64>, end_at: Option<u64>) -> String {
+    match (start_at, end_at) {
+        (Some(start), Some(end)) => format!(" | {} bis {}", format_time(start), format_time(end)),
+        _ => String::new(),
+    }
+}
+
+// Leere Eingabe = Ok(None), unbekannter Wert = Err
+fn parse_car_class(s: &str) -> Result<Option<CarClass>, ()> {
+    match s.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "mini" => Ok(Some(CarClass::Mini)),
+        "compact" => Ok(Some(CarClass::Compact)),
+        "midsize" => Ok(Some(CarClass::Midsize)),
+        "suv" => Ok(Some(CarClass::Suv)),
+        "van" => Ok(Some(CarClass::Van)),
+        _ => Err(()),
+    }
+}
+
+fn parse_transmission(s: &str) -> Result<Option<Transmission>, ()> {
+    match s.trim().to_lowercase().as_str() {
+        "" => Ok(None),
+        "manual" => Ok(Some(Transmission::Manual)),
+        "automatic" => Ok(Some(Transmission::Automatic)),
+        _ => Err(()),
+    }
+}
+
+// "Tag HH:MM", z.B. "12 08:30"; leere Eingabe = Ok(None)
+fn parse_time(s: &str) -> Result<Option<u64>, ()> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    let (day, time) = s.split_once(' ').ok_or(())?;
+    let (hour, minute) = time.trim().split_once(':').ok_or(())?;
+    let day = day.parse::<u64>().map_err(|_| ())?;
+    let hour = hour.parse::<u64>().map_err(|_| ())?;
+    let minute = minute.parse::<u64>().map_err(|_| ())?;
+    if hour >= 24 || minute >= MINUTES_PER_HOUR {
+        return Err(());
+    }
+    Ok(Some(day * MINUTES_PER_DAY + hour * MINUTES_PER_HOUR + minute))
+}
+
+fn parse_features(s: &str) -> Vec<String> {
+    s.split(',').map(|f| f.trim().to_lowercase()).filter(|f| !f.is_empty()).collect()
+}
+
+fn parse_license_class(s: &str) -> Result<LicenseClass, ()> {
+    match s.trim().to_uppercase().as_str() {
+        "B" => Ok(LicenseClass::B),
+        "BE" => Ok(LicenseClass::BE),
+        "C1" => Ok(LicenseClass::C1),
+        "C1E" => Ok(LicenseClass::C1E),
+        "C" => Ok(LicenseClass::C),
+        "CE" => Ok(LicenseClass::CE),
+        "D1" => Ok(LicenseClass::D1),
+        "D" => Ok(LicenseClass::D),
+        _ => Err(()),
+    }
+}
+
+// Abgelaufene Führerscheine sperrt nur die Simulation
+fn parse_block_reason(s: &str) -> Result<BlockReason, ()> {
+    match s.trim().to_lowercase().as_str() {
+        "" | "manual" => Ok(BlockReason::ManualSuspension),
+        "unpaid" => Ok(BlockReason::UnpaidInvoice),
+        "damage" => Ok(BlockReason::Damage),
+        _ => Err(()),
+    }
+}
+
+// "B, BE" -> [B, BE]; leer = nur B
+fn parse_license_classes(s: &str) -> Result<Vec<LicenseClass>, ()> {
+    if s.trim().is_empty() {
+        return Ok(vec![LicenseClass::B]);
+    }
+    s.split(',').map(parse_license_class).collect()
+}
+
+// "mini, compact" -> [Mini, Compact]; leer = alle Klassen
+fn parse_car_classes(s: &str) -> Result<Vec<CarClass>, ()> {
+    s.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| parse_car_class(part)?.ok_or(()))
+        .collect()
+}
+
+// "van=C1, suv=BE" -> [(Van, C1), (Suv, BE)]
+fn parse_license_requirements(s: &str) -> Result<Vec<(CarClass, LicenseClass)>, ()> {
+    s.split(',')
+        .filter(|part| !part.trim().is_empty())
+        .map(|part| {
+            let (class, license) = part.split_once('=').ok_or(())?;
+            let class = parse_car_class(class)?.ok_or(())?;
+            Ok((class, parse_license_class(license)?))
+        })
+        .collect()
+}
+
+// ---------- Einfache SVG-Diagramme für das Dashboard ----------
+
+fn bar_chart(bars: &[(String, u32)]) -> Html {
+    let bar_height = 22;
+    let label_width = 110;
+    let chart_width = 360;
+    let max = bars.iter().map(|(_, v)| *v).max().unwrap_or(0).max(1);
+    let height = bars.len() as u32 * (bar_height + 6);
+
+    html! {
+        <svg width={(label_width + chart_width + 40).to_string()} height={height.to_string()}>
+            { for bars.iter().enumerate().map(|(i, (label, value))| {
+                let y = i as u32 * (bar_height + 6);
+                let w = value * chart_width / max;
+                html! {
+                    <g>
+                        <text x="0" y={(y + 15).to_string()} font-size="13">{label.clone()}</text>
+                        <rect x={label_width.to_string()} y={y.to_string()} width={w.to_string()} height={bar_height.to_string()} rx="4" fill="#333" />
+                        <text x={(label_width + w + 6).to_string()} y={(y + 15).to_string()} font-size="13">{value.to_string()}</text>
+                    </g>
+                }
+            }) }
+        </svg>
+    }
+}
+
+// Beschriftung, Farbe und Wert einer Linie im Verlaufsdiagramm
+type Series = (&'static str, &'static str, fn(&DailySnapshot) -> u32);
+
+fn fleet_chart(history: &[DailySnapshot]) -> Html {
+    if history.is_empty() {
+        return html! { <p style="color:#555; font-size:14px;">{"Noch keine simulierten Tage."}</p> };
+    }
+
+    let width = 520.0;
+    let height = 180.0;
+    let series: [Series; 5] = [
+        ("Available", "#2a9d8f", |s| s.cars.available),
+        ("Rented", "#264653", |s| s.cars.rented),
+        ("Werkstatt/Laden/Umsetzen", "#e9c46a", |s| s.cars.maintenance + s.cars.tuv + s.cars.repair + s.cars.charging + s.cars.relocating),
+        ("Retired", "#e76f51", |s| s.cars.retired),
+        ("Reservations", "#999", |s| s.reservations_pending),
+    ];
+    let max = history
+        .iter()
+        .flat_map(|s| series.iter().map(move |(_, _, f)| f(s)))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    let step = if history.len() > 1 { width / (history.len() - 1) as f32 } else { 0.0 };
+
+    html! {
+        <div>
+            <svg width={(width + 10.0).to_string()} height={(height + 10.0).to_string()}>
+                <rect x="0" y="0" width={(width + 10.0).to_string()} height={(height + 10.0).to_string()} fill="#fafafa" />
+                { for series.iter().map(|(_, color, f)| {
+                    let points: Vec<String> = history.iter().enumerate().map(|(i, s)| {
+                        format!("{:.1},{:.1}", 5.0 + i as f32 * step, 5.0 + height - f(s) as f32 / max * height)
+                    }).collect();
+                    html! { <polyline points={points.join(" ")} fill="none" stroke={*color} stroke-width="2" /> }
+                }) }
+            </svg>
+            <div style="display:flex; gap:12px; flex-wrap:wrap; font-size:13px;">
+                { for series.iter().map(|(label, color, _)| html! {
+                    <span><span style={format!("color:{};", color)}>{"■ "}</span>{*label}</span>
+                }) }
+            </div>
+            <p style="color:#555; font-size:14px;">{format!("Tag {} bis {} (max. {})", history[0].day, history[history.len() - 1].day, max)}</p>
+        </div>
+    }
+}
+
+fn rate_bar(label: &str, rate: f32) -> Html {
+    let width = 360.0;
+    let filled = (rate.clamp(0.0, 1.0) * width) as u32;
+    html! {
+        <div style="margin:6px 0;">
+            <div style="font-size:14px;">{format!("{}: {:.1} %", label, rate * 100.0)}</div>
+            <svg width={(width as u32).to_string()} height="14">
+                <rect x="0" y="0" width={(width as u32).to_string()} height="14" rx="7" fill="#eee" />
+                <rect x="0" y="0" width={filled.to_string()} height="14" rx="7" fill="#333" />
+            </svg>
+        </div>
+    }
+}
+
 #[function_component(App)]
 fn app() -> Html {
     let tab = use_state(|| Tab::Persons);
@@ -49,9 +298,35 @@ fn app() -> Html {
         });
     }
 
+    // Betriebsart der Uhr wird nur einmal geladen (kommt aus der Konfiguration des Backends)
+    let clock = use_state(|| None::<ClockInfo>);
+    {
+        let clock = clock.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                if let Ok(resp) = Request::get("/api/clock").send().await {
+                    if let Ok(item) = resp.json::<ItemEnvelope<ClockInfo>>().await {
+                        clock.set(Some(item.data));
+                    }
+                }
+            });
+            || ()
+        });
+    }
+    let real_time = clock.as_ref().is_some_and(|c| c.mode == "real_time");
+
+    // Im Echtzeitbetrieb regelmäßig neu zeichnen; der Lade-Mechanismus holt dabei Uhrzeit und Version
+    let force_update = use_force_update();
+    use_effect_with(real_time, move |real_time| {
+        let interval = real_time.then(|| Interval::new(REFRESH_MS, move || force_update.force_update()));
+        move || drop(interval)
+    });
+
+    let info = use_state(String::new);
+
     // --- NEUER SPEICHER-MECHANISMUS ---
     let save_state = {
-        let info = use_state(|| String::new());
+        let info = info.clone();
         Callback::from(move |model: CarSharing| {
             let info = info.clone();
             spawn_local(async move {
@@ -59,11 +334,16 @@ fn app() -> Html {
                     .json(&model);
                 
                 match request {
-                    Ok(req) => {
-                        if let Err(_) = req.send().await {
+                    Ok(req) => match req.send().await {
+                        // Inzwischen gespeichert (anderes Fenster, Echtzeituhr): geladener Stand ist veraltet
+                        Ok(resp) if resp.status() == 409 => {
+                            info.set("Nicht gespeichert: Der Zustand wurde inzwischen geändert, bitte Seite neu laden.".to_string());
+                        }
+                        Ok(_) => {}
+                        Err(_) => {
                             info.set("Fehler: Konnte Zustand nicht ans Backend senden.".to_string());
                         }
-                    }
+                    },
                     Err(_) => {
                         info.set("Fehler: Interner Fehler beim Erstellen der Anfrage.".to_string());
                     }
@@ -72,28 +352,97 @@ fn app() -> Html {
         })
     };
 
-    let info = use_state(|| String::new());
-
     // ---------- Form States ----------
     let p_id = use_state(|| "".to_string());
     let p_days = use_state(|| "".to_string());
+    let p_grace = use_state(|| "".to_string());
+    let p_reminders = use_state(|| "".to_string());
+    let p_first = use_state(|| "".to_string());
+    let p_last = use_state(|| "".to_string());
+    let p_email = use_state(|| "".to_string());
+    let p_phone = use_state(|| "".to_string());
+    let p_licenses = use_state(|| "".to_string());
+    let p_age = use_state(|| "".to_string());
+    let p_min_age = use_state(|| "".to_string());
+    let p_requirements = use_state(|| "".to_string());
+    let p_block_reason = use_state(|| "".to_string());
+    let p_block_until = use_state(|| "".to_string());
+    let plan_name = use_state(|| "".to_string());
+    let plan_fee = use_state(|| "".to_string());
+    let plan_per_km = use_state(|| "".to_string());
+    let plan_prio = use_state(|| "".to_string());
+    let plan_max = use_state(|| "".to_string());
+    let plan_classes = use_state(|| "".to_string());
+    let plan_per_hour = use_state(|| "".to_string());
+    let hour_rate = use_state(|| "".to_string());
     let c_id = use_state(|| "".to_string());
     let c_km = use_state(|| "".to_string());
     let c_age = use_state(|| "".to_string());
+    let c_powertrain = use_state(|| "".to_string());
+    let c_station = use_state(|| "".to_string());
+    let c_class = use_state(|| "".to_string());
+    let c_seats = use_state(|| "".to_string());
+    let c_transmission = use_state(|| "".to_string());
+    let c_features = use_state(|| "".to_string());
+    let st_id = use_state(|| "".to_string());
+    let st_name = use_state(|| "".to_string());
+    let st_lat = use_state(|| "".to_string());
+    let st_lon = use_state(|| "".to_string());
+    let st_capacity = use_state(|| "".to_string());
     let r_person = use_state(|| "".to_string());
     let r_car = use_state(|| "".to_string());
-    let r_prio = use_state(|| "1".to_string());
+    let r_class = use_state(|| "".to_string());
+    let r_seats = use_state(|| "".to_string());
+    let r_transmission = use_state(|| "".to_string());
+    let r_features = use_state(|| "".to_string());
+    let r_station = use_state(|| "".to_string());
+    let r_class_id = use_state(|| "".to_string());
+    let r_start = use_state(|| "".to_string());
+    let r_end = use_state(|| "".to_string());
+    let r_strategy = use_state(|| "".to_string());
+    let r_expiry = use_state(|| "".to_string());
+    let r_max_open = use_state(|| "".to_string());
+    let r_max_per_day = use_state(|| "".to_string());
+    let r_cooldown = use_state(|| "".to_string());
+    let r_pickup = use_state(|| "".to_string());
     let ret_person = use_state(|| "".to_string());
     let ret_car = use_state(|| "".to_string());
     let ret_km = use_state(|| "".to_string());
+    let ret_energy = use_state(|| "".to_string());
+    let ret_station = use_state(|| "".to_string());
+    let dmg_severity = use_state(|| "".to_string());
+    let dmg_description = use_state(|| "".to_string());
+    let dmg_location = use_state(|| "".to_string());
+    let dmg_repair_days = use_state(|| "".to_string());
+    let dmg_id = use_state(|| "".to_string());
     let sim_days = use_state(|| "".to_string());
+    let sim_hours = use_state(|| "".to_string());
+    let sc_seed = use_state(|| "".to_string());
+    let sc_days = use_state(|| "".to_string());
+    let sc_rate = use_state(|| "".to_string());
+    let sc_cars = use_state(|| "".to_string());
+    let scenario = use_state(|| None::<ScenarioReport>);
+    let m_day = use_state(|| "".to_string());
+    let m_duration = use_state(|| "".to_string());
+    let m_capacity = use_state(|| "".to_string());
+    let min_energy = use_state(|| "".to_string());
+    let sr_start = use_state(|| "".to_string());
+    let sr_km = use_state(|| "".to_string());
+    let sr_cost = use_state(|| "".to_string());
+    let sr_notes = use_state(|| "".to_string());
 
     let on_reset = {
         let cs = cs.clone();
         let info = info.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
-            let new_model = CarSharing::new();
+            let mut new_model = CarSharing::new();
+            new_model.version = cs.version;
+            // Im Echtzeitbetrieb bleiben Geschäftstag und Uhrzeit erhalten
+            if real_time {
+                new_model.current_day = cs.current_day;
+                new_model.minute_of_day = cs.minute_of_day;
+            }
             save_state.emit(new_model.clone()); // Send reset state to backend
             cs.set(new_model);
             info.set("State an Backend gesendet und zurückgesetzt.".to_string());
@@ -105,7 +454,9 @@ fn app() -> Html {
     let set_tab_cars = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Cars)) };
     let set_tab_res = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Reservations)) };
     let set_tab_rentals = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Rentals)) };
+    let set_tab_stations = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Stations)) };
     let set_tab_sim = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Simulation)) };
+    let set_tab_dashboard = { let tab = tab.clone(); Callback::from(move |_| tab.set(Tab::Dashboard)) };
 
     // ========== Persons Actions ==========
     let on_add_person = {
@@ -113,6 +464,12 @@ fn app() -> Html {
         let info = info.clone();
         let p_id = p_id.clone();
         let p_days = p_days.clone();
+        let p_first = p_first.clone();
+        let p_last = p_last.clone();
+        let p_email = p_email.clone();
+        let p_phone = p_phone.clone();
+        let p_licenses = p_licenses.clone();
+        let p_age = p_age.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
@@ -122,7 +479,30 @@ fn app() -> Html {
                 Ok(v) => v,
                 Err(_) => { info.set("license_valid_days muss eine Zahl sein.".to_string()); return; }
             };
-            let ok = model.register_person(Person { identifier: id.clone(), license_valid_days: days, status: PersonStatus::Active });
+            let license_classes = match parse_license_classes(&p_licenses) {
+                Ok(classes) => classes,
+                Err(_) => { info.set("Führerscheinklassen kommagetrennt angeben, z.B. B, BE, C1.".to_string()); return; }
+            };
+            // Alter in Jahren -> Geburtstag als Simulationstag
+            let birth_day = if (*p_age).trim().is_empty() {
+                None
+            } else {
+                match (*p_age).trim().parse::<u32>() {
+                    Ok(years) => Some((model.current_day as i64 - years as i64 * DAYS_PER_YEAR) as i32),
+                    Err(_) => { info.set("Alter muss eine Zahl sein.".to_string()); return; }
+                }
+            };
+            let optional = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+            let ok = model.register_person(Person {
+                license_valid_days: days,
+                first_name: (*p_first).trim().to_string(),
+                last_name: (*p_last).trim().to_string(),
+                email: optional(&p_email),
+                phone: optional(&p_phone),
+                license_classes,
+                birth_day,
+                ..Person::new(&id)
+            });
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
@@ -178,183 +558,1141 @@ fn app() -> Html {
         })
     };
 
-    // ========== Cars Actions ==========
-    let on_add_car = {
+    let on_suspend_person = {
         let cs = cs.clone();
         let info = info.clone();
-        let c_id = c_id.clone();
-        let c_km = c_km.clone();
-        let c_age = c_age.clone();
+        let p_id = p_id.clone();
+        let p_block_reason = p_block_reason.clone();
+        let p_block_until = p_block_until.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let id = (*c_id).trim().to_string();
-            if id.is_empty() { info.set("Car-ID darf nicht leer sein.".to_string()); return; }
-            let mileage = match (*c_km).trim().parse::<u32>() {
-                Ok(v) => v,
-                Err(_) => { info.set("mileage muss eine Zahl sein.".to_string()); return; }
+            let id = (*p_id).trim().to_string();
+            if id.is_empty() { info.set("Bitte Person-ID eingeben.".to_string()); return; }
+            let reason = match parse_block_reason(&p_block_reason) {
+                Ok(r) => r,
+                Err(_) => { info.set("Sperrgrund: manual, unpaid oder damage.".to_string()); return; }
             };
-            let age_days = match (*c_age).trim().parse::<u32>() {
-                Ok(v) => v,
-                Err(_) => { info.set("age_days muss eine Zahl sein.".to_string()); return; }
+            let until_day = if (*p_block_until).trim().is_empty() {
+                None
+            } else {
+                match (*p_block_until).trim().parse::<u32>() {
+                    Ok(v) => Some(v),
+                    Err(_) => { info.set("Sperre bis Tag muss eine Zahl sein.".to_string()); return; }
+                }
             };
-            let ok = model.register_car(Car { identifier: id.clone(), mileage, status: CarStatus::Available, age_days, rental_count: 0 });
+            let ok = model.suspend_person(&id, reason, until_day);
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
-                info.set(format!("Auto '{}' angelegt.", id));
+                info.set(format!("Person '{}' gesperrt ({:?}).", id, reason));
             } else {
-                info.set("Auto konnte nicht angelegt werden.".to_string());
+                info.set("Person nicht gefunden oder Enddatum liegt nicht in der Zukunft.".to_string());
             }
         })
     };
 
-    let on_remove_car = {
+    let on_unsuspend_person = {
         let cs = cs.clone();
         let info = info.clone();
-        let c_id = c_id.clone();
+        let p_id = p_id.clone();
+        let p_block_reason = p_block_reason.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let id = (*c_id).trim().to_string();
-            if id.is_empty() { info.set("Zum Entfernen bitte Car-ID eingeben.".to_string()); return; }
-            let ok = model.unregister_car(&id);
+            let id = (*p_id).trim().to_string();
+            if id.is_empty() { info.set("Bitte Person-ID eingeben.".to_string()); return; }
+            let reason = match parse_block_reason(&p_block_reason) {
+                Ok(r) => r,
+                Err(_) => { info.set("Sperrgrund: manual, unpaid oder damage.".to_string()); return; }
+            };
+            let ok = model.unsuspend_person(&id, reason);
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
-                info.set(format!("Auto '{}' entfernt.", id));
+                info.set(format!("Sperre {:?} für '{}' aufgehoben.", reason, id));
             } else {
-                info.set("Auto konnte nicht entfernt werden.".to_string());
+                info.set("Person nicht gefunden oder nicht aus diesem Grund gesperrt.".to_string());
             }
         })
     };
 
-    // ========== Reservation Actions ==========
-    let on_reserve = {
+    let on_add_plan = {
         let cs = cs.clone();
         let info = info.clone();
-        let r_person = r_person.clone();
-        let r_car = r_car.clone();
-        let r_prio = r_prio.clone();
+        let plan_name = plan_name.clone();
+        let plan_fee = plan_fee.clone();
+        let plan_per_km = plan_per_km.clone();
+        let plan_prio = plan_prio.clone();
+        let plan_max = plan_max.clone();
+        let plan_classes = plan_classes.clone();
+        let plan_per_hour = plan_per_hour.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let person_id = (*r_person).trim().to_string();
-            let car_id = (*r_car).trim().to_string();
-            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
-            let prio = match (*r_prio).trim().parse::<u32>() {
-                Ok(v) => v,
-                Err(_) => { info.set("priority muss eine Zahl sein.".to_string()); return; }
+            let name = (*plan_name).trim().to_string();
+            if name.is_empty() { info.set("Bitte Tarifname eingeben.".to_string()); return; }
+            let numbers: Result<Vec<u32>, _> = [&plan_fee, &plan_per_km, &plan_prio, &plan_max]
+                .iter()
+                .map(|v| v.trim().parse::<u32>())
+                .collect();
+            let numbers = match numbers {
+                Ok(n) => n,
+                Err(_) => { info.set("Gebühr, km-Preis (Cent), Priorität und max. Reservierungen müssen Zahlen sein.".to_string()); return; }
             };
-            let ok = model.reserve_car(&person_id, &car_id, prio);
+            let allowed_classes = match parse_car_classes(&plan_classes) {
+                Ok(classes) => classes,
+                Err(_) => { info.set("Klassen kommagetrennt angeben, z.B. mini, compact (leer = alle).".to_string()); return; }
+            };
+            // Leer = Standard-Zeitpreis der Flotte
+            let per_hour_cents = match (*plan_per_hour).trim() {
+                "" => None,
+                v => match v.parse::<u32>() {
+                    Ok(cents) => Some(cents),
+                    Err(_) => { info.set("Preis je Stunde muss eine Zahl sein (Cent).".to_string()); return; }
+                },
+            };
+            let ok = model.register_plan(MembershipPlan {
+                name: name.clone(),
+                monthly_fee_cents: numbers[0],
+                per_km_cents: numbers[1],
+                per_hour_cents,
+                priority: numbers[2],
+                max_reservations: numbers[3],
+                allowed_classes,
+            });
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
-                info.set(format!("Reservierung gesetzt: {} -> {}.", person_id, car_id));
+                info.set(format!("Tarif '{}' angelegt.", name));
             } else {
-                info.set("Reservierung nicht möglich.".to_string());
+                info.set("Tarif existiert schon.".to_string());
             }
         })
     };
 
-    let on_cancel_reservation = {
+    let on_remove_plan = {
         let cs = cs.clone();
         let info = info.clone();
-        let r_person = r_person.clone();
-        let r_car = r_car.clone();
+        let plan_name = plan_name.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let person_id = (*r_person).trim().to_string();
-            let car_id = (*r_car).trim().to_string();
-            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
-            let ok = model.cancel_reservation(&person_id, &car_id);
+            let name = (*plan_name).trim().to_string();
+            let ok = model.unregister_plan(&name);
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
-                info.set(format!("Reservierung storniert: {} -> {}.", person_id, car_id));
+                info.set(format!("Tarif '{}' entfernt.", name));
             } else {
-                info.set("Reservierung nicht gefunden.".to_string());
+                info.set("Tarif nicht gefunden oder noch gebucht.".to_string());
             }
         })
     };
 
-    let on_process_reservations = {
+    let on_set_hour_rate = {
         let cs = cs.clone();
         let info = info.clone();
+        let hour_rate = hour_rate.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let processed = model.process_reservations();
+            let cents = match hour_rate.trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Zeitpreis muss eine Zahl sein (Cent je Stunde).".to_string()); return; }
+            };
+            model.policy.per_hour_cents = cents;
             save_state.emit(model.clone());
             cs.set(model);
-            if processed.is_empty() {
-                info.set("Keine Reservierungen verarbeitet.".to_string());
+            info.set(format!("Standard-Zeitpreis: {:.2} € je angefangene Stunde.", cents as f32 / 100.0));
+        })
+    };
+
+    let on_subscribe = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let p_id = p_id.clone();
+        let plan_name = plan_name.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*p_id).trim().to_string();
+            let name = (*plan_name).trim().to_string();
+            if id.is_empty() || name.is_empty() { info.set("Bitte Person-ID und Tarifname eingeben.".to_string()); return; }
+            let ok = model.subscribe_person(&id, &name);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("'{}' hat Tarif '{}' gebucht.", id, name));
             } else {
-                info.set(format!("Verarbeitet: {:?}", processed));
+                info.set("Person oder Tarif nicht gefunden, oder Tarif schon gebucht.".to_string());
             }
         })
     };
 
-    // ========== Return Action ==========
-    let on_return = {
+    let on_unsubscribe = {
         let cs = cs.clone();
         let info = info.clone();
-        let ret_person = ret_person.clone();
-        let ret_car = ret_car.clone();
-        let ret_km = ret_km.clone();
+        let p_id = p_id.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let person_id = (*ret_person).trim().to_string();
-            let car_id = (*ret_car).trim().to_string();
-            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
-            let driven_km = match (*ret_km).trim().parse::<u32>() {
+            let id = (*p_id).trim().to_string();
+            let ok = model.unsubscribe_person(&id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Tarif von '{}' gekündigt.", id));
+            } else {
+                info.set("Person nicht gefunden oder ohne Tarif.".to_string());
+            }
+        })
+    };
+
+    let on_set_license_policy = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let p_grace = p_grace.clone();
+        let p_reminders = p_reminders.clone();
+        let p_min_age = p_min_age.clone();
+        let p_requirements = p_requirements.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            if !(*p_min_age).trim().is_empty() {
+                match (*p_min_age).trim().parse::<u32>() {
+                    Ok(v) => model.policy.min_age_years = v,
+                    Err(_) => { info.set("Mindestalter muss eine Zahl sein.".to_string()); return; }
+                }
+            }
+            if !(*p_requirements).trim().is_empty() {
+                match parse_license_requirements(&p_requirements) {
+                    Ok(requirements) => model.policy.license_requirements = requirements,
+                    Err(_) => { info.set("Klassen als Fahrzeugklasse=Führerscheinklasse angeben, z.B. van=C1, suv=BE.".to_string()); return; }
+                }
+            }
+            if !(*p_grace).trim().is_empty() {
+                match (*p_grace).trim().parse::<u32>() {
+                    Ok(v) => model.policy.license_grace_days = v,
+                    Err(_) => { info.set("Kulanzfrist muss eine Zahl sein.".to_string()); return; }
+                }
+            }
+            if !(*p_reminders).trim().is_empty() {
+                match (*p_reminders).split(',').map(|d| d.trim().parse::<u32>()).collect::<Result<Vec<u32>, _>>() {
+                    Ok(days) => model.policy.license_reminder_days = days,
+                    Err(_) => { info.set("Erinnerungen als kommagetrennte Tage angeben, z.B. 30,7,1.".to_string()); return; }
+                }
+            }
+            save_state.emit(model.clone());
+            info.set(format!(
+                "Kulanzfrist: {} Tage, Erinnerungen: {:?}, Mindestalter: {}, Klassen: {:?}",
+                model.policy.license_grace_days, model.policy.license_reminder_days, model.policy.min_age_years, model.policy.license_requirements
+            ));
+            cs.set(model);
+        })
+    };
+
+    // ========== Cars Actions ==========
+    let on_add_car = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let c_km = c_km.clone();
+        let c_age = c_age.clone();
+        let c_powertrain = c_powertrain.clone();
+        let c_station = c_station.clone();
+        let c_class = c_class.clone();
+        let c_seats = c_seats.clone();
+        let c_transmission = c_transmission.clone();
+        let c_features = c_features.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            if id.is_empty() { info.set("Car-ID darf nicht leer sein.".to_string()); return; }
+            let mileage = match (*c_km).trim().parse::<u32>() {
                 Ok(v) => v,
-                Err(_) => { info.set("driven_km muss eine Zahl sein.".to_string()); return; }
+                Err(_) => { info.set("mileage muss eine Zahl sein.".to_string()); return; }
+            };
+            let age_days = match (*c_age).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("age_days muss eine Zahl sein.".to_string()); return; }
+            };
+            let powertrain = match (*c_powertrain).trim().to_lowercase().as_str() {
+                "" | "combustion" => Powertrain::Combustion,
+                "electric" => Powertrain::Electric,
+                _ => { info.set("powertrain muss combustion oder electric sein.".to_string()); return; }
+            };
+            let class = match parse_car_class(&c_class) {
+                Ok(class) => class.unwrap_or_default(),
+                Err(_) => { info.set("Klasse muss mini, compact, midsize, suv oder van sein.".to_string()); return; }
+            };
+            let seats = match (*c_seats).trim() {
+                "" => DEFAULT_SEATS,
+                s => match s.parse::<u8>() {
+                    Ok(v) => v,
+                    Err(_) => { info.set("Sitze müssen eine Zahl sein.".to_string()); return; }
+                },
             };
-            let ok = model.return_car(&person_id, &car_id, driven_km);
+            let transmission = match parse_transmission(&c_transmission) {
+                Ok(t) => t.unwrap_or_default(),
+                Err(_) => { info.set("Getriebe muss manual oder automatic sein.".to_string()); return; }
+            };
+            let ok = model.register_car(Car {
+                mileage,
+                age_days,
+                powertrain,
+                station_id: Some((*c_station).trim().to_string()).filter(|s| !s.is_empty()),
+                class,
+                seats,
+                transmission,
+                features: parse_features(&c_features),
+                ..Car::new(&id)
+            });
             if ok {
                 save_state.emit(model.clone());
                 cs.set(model);
-                info.set(format!("Auto zurückgegeben: {} -> {}.", person_id, car_id));
+                info.set(format!("Auto '{}' angelegt.", id));
             } else {
-                info.set("Return fehlgeschlagen.".to_string());
+                info.set("Auto konnte nicht angelegt werden (Station unbekannt oder voll?).".to_string());
             }
         })
     };
 
-    // ========== Simulation ==========
-    let on_simulate = {
+    let on_remove_car = {
         let cs = cs.clone();
         let info = info.clone();
-        let sim_days = sim_days.clone();
+        let c_id = c_id.clone();
         let save_state = save_state.clone();
         Callback::from(move |_| {
             let mut model = (*cs).clone();
-            let n = match sim_days.trim().parse::<u32>() {
+            let id = (*c_id).trim().to_string();
+            if id.is_empty() { info.set("Zum Entfernen bitte Car-ID eingeben.".to_string()); return; }
+            let ok = model.unregister_car(&id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Auto '{}' entfernt.", id));
+            } else {
+                info.set("Auto konnte nicht entfernt werden.".to_string());
+            }
+        })
+    };
+
+    // ========== Workshop Actions ==========
+    let make_schedule = |kind: ServiceKind| {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let m_day = m_day.clone();
+        let m_duration = m_duration.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            if id.is_empty() { info.set("Bitte Car-ID eingeben.".to_string()); return; }
+            let day = match (*m_day).trim().parse::<u32>() {
                 Ok(v) => v,
-                Err(_) => { info.set("Simulationstage müssen eine Zahl sein.".to_string()); return; }
+                Err(_) => { info.set("Tag muss eine Zahl sein.".to_string()); return; }
             };
-            model.simulate_n_days(n);
-            save_state.emit(model.clone());
-            cs.set(model);
-            info.set(format!("Simulation durchgeführt: {} Tage.", n));
+            let duration = match (*m_duration).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Dauer muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.schedule_maintenance(&id, kind, day, duration);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("{:?} für '{}' an Tag {} geplant.", kind, id, day));
+            } else {
+                info.set("Termin konnte nicht geplant werden (Tag in der Zukunft?).".to_string());
+            }
         })
     };
+    let on_schedule_maintenance = make_schedule(ServiceKind::Maintenance);
+    let on_schedule_tuv = make_schedule(ServiceKind::Tuv);
 
-    // ========== Inputs: oninput callbacks ==========
-    let on_p_id = { let p_id = p_id.clone(); Callback::from(move |e: InputEvent| { p_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let make_start = |kind: ServiceKind| {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let m_duration = m_duration.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            if id.is_empty() { info.set("Bitte Car-ID eingeben.".to_string()); return; }
+            let duration = match (*m_duration).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Dauer muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.start_maintenance(&id, kind, duration);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("{:?} für '{}' gestartet ({} Tage).", kind, id, duration));
+            } else {
+                info.set("Start nicht möglich (Auto nicht verfügbar oder Werkstatt voll).".to_string());
+            }
+        })
+    };
+    let on_start_maintenance = make_start(ServiceKind::Maintenance);
+    let on_start_tuv = make_start(ServiceKind::Tuv);
+
+    let on_extend_maintenance = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let m_duration = m_duration.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            let extra = match (*m_duration).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Dauer muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.extend_maintenance(&id, extra);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Werkstattaufenthalt von '{}' um {} Tage verlängert.", id, extra));
+            } else {
+                info.set("Auto ist nicht in der Werkstatt.".to_string());
+            }
+        })
+    };
+
+    let on_end_maintenance = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            let ok = model.end_maintenance(&id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Werkstattaufenthalt von '{}' beendet.", id));
+            } else {
+                info.set("Auto ist nicht in der Werkstatt.".to_string());
+            }
+        })
+    };
+
+    let on_set_capacity = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let m_capacity = m_capacity.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let input = (*m_capacity).trim().to_string();
+            if input.is_empty() {
+                model.policy.workshop_capacity = None;
+            } else {
+                match input.parse::<u32>() {
+                    Ok(v) => model.policy.workshop_capacity = Some(v),
+                    Err(_) => { info.set("Kapazität muss eine Zahl sein (leer = unbegrenzt).".to_string()); return; }
+                }
+            }
+            save_state.emit(model.clone());
+            info.set(format!("Werkstattkapazität: {:?}", model.policy.workshop_capacity));
+            cs.set(model);
+        })
+    };
+
+    let on_set_min_energy = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let min_energy = min_energy.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            match (*min_energy).trim().parse::<u8>() {
+                Ok(v) if v <= FULL_ENERGY_LEVEL => model.policy.min_energy_level = v,
+                _ => { info.set("Mindestfüllstand muss zwischen 0 und 100 liegen.".to_string()); return; }
+            }
+            save_state.emit(model.clone());
+            info.set(format!("Mindestfüllstand: {} %", model.policy.min_energy_level));
+            cs.set(model);
+        })
+    };
+
+    // Manueller Nachweis: Start-Tag aus sr_start, End-Tag aus m_day
+    let make_record = |kind: ServiceKind| {
+        let cs = cs.clone();
+        let info = info.clone();
+        let c_id = c_id.clone();
+        let m_day = m_day.clone();
+        let sr_start = sr_start.clone();
+        let sr_km = sr_km.clone();
+        let sr_cost = sr_cost.clone();
+        let sr_notes = sr_notes.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*c_id).trim().to_string();
+            if id.is_empty() { info.set("Bitte Car-ID eingeben.".to_string()); return; }
+            let (start_day, end_day, mileage, cost_cents) = match (
+                (*sr_start).trim().parse::<u32>(),
+                (*m_day).trim().parse::<u32>(),
+                (*sr_km).trim().parse::<u32>(),
+                (*sr_cost).trim().parse::<u32>(),
+            ) {
+                (Ok(a), Ok(b), Ok(c), Ok(d)) => (a, b, c, d),
+                _ => { info.set("Start-Tag, Tag, km und Kosten müssen Zahlen sein.".to_string()); return; }
+            };
+            let ok = model.add_service_record(ServiceRecord {
+                car_id: id.clone(),
+                kind,
+                start_day,
+                end_day,
+                mileage,
+                notes: (*sr_notes).trim().to_string(),
+                cost_cents,
+            });
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("{:?}-Nachweis für '{}' gespeichert.", kind, id));
+            } else {
+                info.set("Nachweis ungültig (Auto unbekannt oder Tage falsch).".to_string());
+            }
+        })
+    };
+    let on_record_maintenance = make_record(ServiceKind::Maintenance);
+    let on_record_tuv = make_record(ServiceKind::Tuv);
+
+    // ========== Reservation Actions ==========
+    let on_reserve = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_person = r_person.clone();
+        let r_car = r_car.clone();
+        let r_start = r_start.clone();
+        let r_end = r_end.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let person_id = (*r_person).trim().to_string();
+            let car_id = (*r_car).trim().to_string();
+            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
+            let ok = match (parse_time(&r_start), parse_time(&r_end)) {
+                (Ok(None), Ok(None)) => model.reserve_car(&person_id, &car_id),
+                (Ok(Some(start)), Ok(Some(end))) => model.reserve_car_between(&person_id, &car_id, start, end),
+                _ => { info.set("Zeitfenster als 'Tag HH:MM' angeben, Beginn und Ende oder beides leer.".to_string()); return; }
+            };
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Reservierung gesetzt: {} -> {}.", person_id, car_id));
+            } else {
+                info.set("Reservierung nicht möglich (gesperrt, nicht im Tarif, Obergrenze erreicht oder Zeitfenster ungültig?).".to_string());
+            }
+        })
+    };
+
+    let on_cancel_reservation = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_person = r_person.clone();
+        let r_car = r_car.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let person_id = (*r_person).trim().to_string();
+            let car_id = (*r_car).trim().to_string();
+            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
+            let ok = model.cancel_reservation(&person_id, &car_id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Reservierung storniert: {} -> {}.", person_id, car_id));
+            } else {
+                info.set("Reservierung nicht gefunden.".to_string());
+            }
+        })
+    };
+
+    let on_reserve_class = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_person = r_person.clone();
+        let r_class = r_class.clone();
+        let r_seats = r_seats.clone();
+        let r_transmission = r_transmission.clone();
+        let r_features = r_features.clone();
+        let r_station = r_station.clone();
+        let r_start = r_start.clone();
+        let r_end = r_end.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let person_id = (*r_person).trim().to_string();
+            if person_id.is_empty() { info.set("Bitte Person-ID eingeben.".to_string()); return; }
+            let window = match (parse_time(&r_start), parse_time(&r_end)) {
+                (Ok(None), Ok(None)) => None,
+                (Ok(Some(start)), Ok(Some(end))) => Some((start, end)),
+                _ => { info.set("Zeitfenster als 'Tag HH:MM' angeben, Beginn und Ende oder beides leer.".to_string()); return; }
+            };
+            let class = match parse_car_class(&r_class) {
+                Ok(class) => class,
+                Err(_) => { info.set("Klasse muss mini, compact, midsize, suv oder van sein.".to_string()); return; }
+            };
+            let min_seats = match (*r_seats).trim() {
+                "" => None,
+                s => match s.parse::<u8>() {
+                    Ok(v) => Some(v),
+                    Err(_) => { info.set("Sitze müssen eine Zahl sein.".to_string()); return; }
+                },
+            };
+            let transmission = match parse_transmission(&r_transmission) {
+                Ok(t) => t,
+                Err(_) => { info.set("Getriebe muss manual oder automatic sein.".to_string()); return; }
+            };
+            let filter = CarFilter {
+                class,
+                min_seats,
+                transmission,
+                powertrain: None,
+                features: parse_features(&r_features),
+                station_id: Some((*r_station).trim().to_string()).filter(|s| !s.is_empty()),
+            };
+            let ok = match window {
+                Some((start, end)) => model.reserve_class_between(&person_id, filter, start, end),
+                None => model.reserve_class(&person_id, filter),
+            };
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Reservierung nach Merkmalen für '{}' gesetzt.", person_id));
+            } else {
+                info.set("Reservierung nicht möglich (Person gesperrt, Obergrenze des Tarifs erreicht oder kein passendes Auto in der Flotte?).".to_string());
+            }
+        })
+    };
+
+    let on_cancel_class_reservation = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_class_id = r_class_id.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = match (*r_class_id).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Reservierungs-Nr. muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.cancel_class_reservation(id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Reservierung #{} storniert.", id));
+            } else {
+                info.set("Reservierung nicht gefunden.".to_string());
+            }
+        })
+    };
+
+    let on_set_strategy = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_strategy = r_strategy.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            model.policy.allocation = match (*r_strategy).trim().to_lowercase().as_str() {
+                "priority" => AllocationStrategy::StrictPriority,
+                "fifo" => AllocationStrategy::Fifo,
+                "aging" => AllocationStrategy::PriorityWithAging,
+                "matching" => AllocationStrategy::MaximumMatching,
+                _ => { info.set("Strategie muss priority, fifo, aging oder matching sein.".to_string()); return; }
+            };
+            save_state.emit(model.clone());
+            info.set(format!("Zuteilung: {:?}", model.policy.allocation));
+            cs.set(model);
+        })
+    };
+
+    let on_set_expiry = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_expiry = r_expiry.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let input = (*r_expiry).trim().to_string();
+            if input.is_empty() {
+                model.policy.reservation_expiry_days = None;
+            } else {
+                match input.parse::<u32>() {
+                    Ok(v) => model.policy.reservation_expiry_days = Some(v),
+                    Err(_) => { info.set("Verfall muss eine Zahl sein (leer = nie).".to_string()); return; }
+                }
+            }
+            save_state.emit(model.clone());
+            info.set(format!("Reservierungen verfallen nach: {:?} Tagen", model.policy.reservation_expiry_days));
+            cs.set(model);
+        })
+    };
+
+    let on_set_quotas = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let r_max_open = r_max_open.clone();
+        let r_max_per_day = r_max_per_day.clone();
+        let r_cooldown = r_cooldown.clone();
+        let r_pickup = r_pickup.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            // Leer = unbegrenzt bzw. keine Abholfrist
+            let limit = |s: &str| -> Result<Option<u32>, ()> {
+                match s.trim() {
+                    "" => Ok(None),
+                    v => v.parse::<u32>().map(Some).map_err(|_| ()),
+                }
+            };
+            let (max_open, max_per_day, pickup) = match (limit(&r_max_open), limit(&r_max_per_day), limit(&r_pickup)) {
+                (Ok(a), Ok(b), Ok(c)) => (a, b, c),
+                _ => { info.set("Grenzen und Abholfrist müssen Zahlen sein (leer = unbegrenzt).".to_string()); return; }
+            };
+            let cooldown = match (*r_cooldown).trim() {
+                "" => model.policy.no_show_cooldown_days,
+                v => match v.parse::<u32>() {
+                    Ok(days) => days,
+                    Err(_) => { info.set("Sperrfrist muss eine Zahl sein.".to_string()); return; }
+                },
+            };
+            model.policy.max_open_reservations = max_open;
+            model.policy.max_reservations_per_day = max_per_day;
+            model.policy.no_show_cooldown_days = cooldown;
+            model.policy.pickup_deadline_days = pickup;
+            save_state.emit(model.clone());
+            info.set(format!(
+                "Reservierungsgrenzen: offen {:?}, je Tag {:?}, Sperrfrist nach No-Show {} Tage, Abholfrist {:?} Tage",
+                max_open, max_per_day, cooldown, pickup
+            ));
+            cs.set(model);
+        })
+    };
+
+    let on_toggle_retired_rule = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            model.policy.on_car_retired = match model.policy.on_car_retired {
+                RetiredCarRule::Cancel => RetiredCarRule::Reassign,
+                RetiredCarRule::Reassign => RetiredCarRule::Cancel,
+            };
+            save_state.emit(model.clone());
+            info.set(format!("Ausgemustertes Auto: {:?}", model.policy.on_car_retired));
+            cs.set(model);
+        })
+    };
+
+    let on_toggle_blocked_rule = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            model.policy.on_person_blocked = match model.policy.on_person_blocked {
+                BlockedPersonRule::Cancel => BlockedPersonRule::Suspend,
+                BlockedPersonRule::Suspend => BlockedPersonRule::Cancel,
+            };
+            save_state.emit(model.clone());
+            info.set(format!("Gesperrte Person: {:?}", model.policy.on_person_blocked));
+            cs.set(model);
+        })
+    };
+
+    let on_process_reservations = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let processed = model.process_reservations();
+            save_state.emit(model.clone());
+            cs.set(model);
+            if processed.is_empty() {
+                info.set("Keine Reservierungen verarbeitet.".to_string());
+            } else {
+                info.set(format!("Verarbeitet: {:?}", processed));
+            }
+        })
+    };
+
+    // ========== Return Action ==========
+    let on_return = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let ret_person = ret_person.clone();
+        let ret_car = ret_car.clone();
+        let ret_km = ret_km.clone();
+        let ret_energy = ret_energy.clone();
+        let ret_station = ret_station.clone();
+        let dmg_severity = dmg_severity.clone();
+        let dmg_description = dmg_description.clone();
+        let dmg_location = dmg_location.clone();
+        let dmg_repair_days = dmg_repair_days.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let person_id = (*ret_person).trim().to_string();
+            let car_id = (*ret_car).trim().to_string();
+            if person_id.is_empty() || car_id.is_empty() { info.set("Bitte Person-ID und Car-ID eingeben.".to_string()); return; }
+            let driven_km = match (*ret_km).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("driven_km muss eine Zahl sein.".to_string()); return; }
+            };
+            let energy_level = match (*ret_energy).trim() {
+                "" => None,
+                v => match v.parse::<u8>() {
+                    Ok(level) if level <= FULL_ENERGY_LEVEL => Some(level),
+                    _ => { info.set("Füllstand muss zwischen 0 und 100 liegen.".to_string()); return; }
+                },
+            };
+            // Leere Schwere = kein Schaden
+            let severity = match (*dmg_severity).trim().to_lowercase().as_str() {
+                "" => None,
+                "minor" => Some(DamageSeverity::Minor),
+                "major" => Some(DamageSeverity::Major),
+                "breakdown" => Some(DamageSeverity::Breakdown),
+                _ => { info.set("Schwere muss minor, major oder breakdown sein.".to_string()); return; }
+            };
+            let repair_days = match (*dmg_repair_days).trim() {
+                "" => None,
+                v => match v.parse::<u32>() {
+                    Ok(d) => Some(d),
+                    Err(_) => { info.set("Reparaturtage müssen eine Zahl sein.".to_string()); return; }
+                },
+            };
+            let damage = severity.map(|severity| DamageReport {
+                severity,
+                description: (*dmg_description).trim().to_string(),
+                location: (*dmg_location).trim().to_string(),
+                repair_days,
+            });
+            let details = ReturnDetails {
+                energy_level,
+                damage,
+                station_id: Some((*ret_station).trim().to_string()).filter(|s| !s.is_empty()),
+            };
+            let ok = model.return_car(&person_id, &car_id, driven_km, details);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Auto zurückgegeben: {} -> {}.", person_id, car_id));
+            } else {
+                info.set("Return fehlgeschlagen (Station voll?).".to_string());
+            }
+        })
+    };
+
+    let on_pick_up = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let ret_person = ret_person.clone();
+        let ret_car = ret_car.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let person_id = (*ret_person).trim().to_string();
+            let car_id = (*ret_car).trim().to_string();
+            if model.pick_up_car(&person_id, &car_id) {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Auto abgeholt: {} -> {}.", person_id, car_id));
+            } else {
+                info.set("Keine Ausleihe, die noch abgeholt werden muss.".to_string());
+            }
+        })
+    };
+
+    let on_resolve_damage = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let dmg_id = dmg_id.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = match (*dmg_id).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Schadens-ID muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.resolve_damage(id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Schaden #{} als behoben markiert.", id));
+            } else {
+                info.set("Offener Schaden nicht gefunden.".to_string());
+            }
+        })
+    };
+
+    // ========== Station Actions ==========
+    let on_add_station = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let st_id = st_id.clone();
+        let st_name = st_name.clone();
+        let st_lat = st_lat.clone();
+        let st_lon = st_lon.clone();
+        let st_capacity = st_capacity.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*st_id).trim().to_string();
+            if id.is_empty() { info.set("Station-ID darf nicht leer sein.".to_string()); return; }
+            let (latitude, longitude) = match ((*st_lat).trim().parse::<f64>(), (*st_lon).trim().parse::<f64>()) {
+                (Ok(lat), Ok(lon)) => (lat, lon),
+                _ => { info.set("Breiten- und Längengrad müssen Zahlen sein.".to_string()); return; }
+            };
+            let capacity = match (*st_capacity).trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Kapazität muss eine Zahl sein.".to_string()); return; }
+            };
+            let ok = model.register_station(Station { identifier: id.clone(), name: (*st_name).trim().to_string(), latitude, longitude, capacity });
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Station '{}' angelegt.", id));
+            } else {
+                info.set("Station existiert schon.".to_string());
+            }
+        })
+    };
+
+    let on_remove_station = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let st_id = st_id.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let id = (*st_id).trim().to_string();
+            let ok = model.unregister_station(&id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Station '{}' entfernt.", id));
+            } else {
+                info.set("Station nicht gefunden oder nicht leer.".to_string());
+            }
+        })
+    };
+
+    let on_move_car = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let st_id = st_id.clone();
+        let c_id = c_id.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let station_id = (*st_id).trim().to_string();
+            let car_id = (*c_id).trim().to_string();
+            let ok = model.move_car_to_station(&car_id, &station_id);
+            if ok {
+                save_state.emit(model.clone());
+                cs.set(model);
+                info.set(format!("Auto '{}' steht jetzt an Station '{}'.", car_id, station_id));
+            } else {
+                info.set("Umsetzen nicht möglich (Auto vermietet oder Station voll).".to_string());
+            }
+        })
+    };
+
+    let on_apply_rebalancing = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            let mut model = (*cs).clone();
+            let plan = plan_rebalancing(&model);
+            if plan.moves.is_empty() {
+                info.set("Keine Umsetzungen nötig.".to_string());
+                return;
+            }
+            let started = apply_rebalancing(&mut model, &plan);
+            save_state.emit(model.clone());
+            cs.set(model);
+            info.set(format!("{} von {} Umsetzungen gestartet.", started, plan.moves.len()));
+        })
+    };
+
+    // ========== Simulation ==========
+    let on_simulate = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let sim_days = sim_days.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            if real_time {
+                info.set("Echtzeitbetrieb: Der Tag wechselt automatisch um Mitternacht.".to_string());
+                return;
+            }
+            let mut model = (*cs).clone();
+            let n = match sim_days.trim().parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Simulationstage müssen eine Zahl sein.".to_string()); return; }
+            };
+            model.simulate_n_days(n);
+            save_state.emit(model.clone());
+            cs.set(model);
+            info.set(format!("Simulation durchgeführt: {} Tage.", n));
+        })
+    };
+
+    let on_advance_hours = {
+        let cs = cs.clone();
+        let info = info.clone();
+        let sim_hours = sim_hours.clone();
+        let save_state = save_state.clone();
+        Callback::from(move |_| {
+            if real_time {
+                info.set("Echtzeitbetrieb: Die Uhrzeit folgt der Ortszeit des Backends.".to_string());
+                return;
+            }
+            let mut model = (*cs).clone();
+            let n = match sim_hours.trim().parse::<u64>() {
+                Ok(v) => v,
+                Err(_) => { info.set("Stunden müssen eine Zahl sein.".to_string()); return; }
+            };
+            model.advance_minutes(n * MINUTES_PER_HOUR);
+            save_state.emit(model.clone());
+            info.set(format!("Uhr um {} Stunden vorgestellt: {}.", n, format_time(model.now())));
+            cs.set(model);
+        })
+    };
+
+    // Was-wäre-wenn: rechnet im Backend auf einer Kopie, der Zustand bleibt unverändert
+    let on_run_scenario = {
+        let info = info.clone();
+        let sc_seed = sc_seed.clone();
+        let sc_days = sc_days.clone();
+        let sc_rate = sc_rate.clone();
+        let sc_cars = sc_cars.clone();
+        let scenario = scenario.clone();
+        Callback::from(move |_| {
+            let numbers: Result<Vec<u64>, _> = [&sc_seed, &sc_days, &sc_cars].iter().map(|v| v.trim().parse::<u64>()).collect();
+            let (numbers, rate) = match (numbers, sc_rate.trim().parse::<f64>()) {
+                (Ok(n), Ok(rate)) => (n, rate),
+                _ => { info.set("Seed, Tage, Wünsche je Tag und zusätzliche Autos müssen Zahlen sein.".to_string()); return; }
+            };
+            let body = serde_json::json!({
+                "seed": numbers[0],
+                "days": numbers[1],
+                "requests_per_day": rate,
+                "extra_cars": numbers[2],
+            });
+            let info = info.clone();
+            let scenario = scenario.clone();
+            info.set("Szenario wird gerechnet ...".to_string());
+            spawn_local(async move {
+                let request = match Request::post("/api/scenarios").json(&body) {
+                    Ok(req) => req,
+                    Err(_) => { info.set("Fehler: Interner Fehler beim Erstellen der Anfrage.".to_string()); return; }
+                };
+                match request.send().await {
+                    Ok(resp) if resp.ok() => match resp.json::<ItemEnvelope<ScenarioReport>>().await {
+                        Ok(item) => {
+                            info.set(format!("Szenario gerechnet: Tag {} bis {}.", item.data.start_day, item.data.end_day));
+                            scenario.set(Some(item.data));
+                        }
+                        Err(_) => info.set("Fehler: Antwort des Szenarios nicht lesbar.".to_string()),
+                    },
+                    Ok(resp) => info.set(format!("Szenario abgelehnt: {}", resp.text().await.unwrap_or_default())),
+                    Err(_) => info.set("Fehler: Konnte Szenario nicht ans Backend senden.".to_string()),
+                }
+            });
+        })
+    };
+
+    // ========== Inputs: oninput callbacks ==========
+    let on_p_id = { let p_id = p_id.clone(); Callback::from(move |e: InputEvent| { p_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_p_days = { let p_days = p_days.clone(); Callback::from(move |e: InputEvent| { p_days.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_grace = { let p_grace = p_grace.clone(); Callback::from(move |e: InputEvent| { p_grace.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_first = { let p_first = p_first.clone(); Callback::from(move |e: InputEvent| { p_first.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_last = { let p_last = p_last.clone(); Callback::from(move |e: InputEvent| { p_last.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_email = { let p_email = p_email.clone(); Callback::from(move |e: InputEvent| { p_email.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_phone = { let p_phone = p_phone.clone(); Callback::from(move |e: InputEvent| { p_phone.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_licenses = { let p_licenses = p_licenses.clone(); Callback::from(move |e: InputEvent| { p_licenses.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_block_reason = { let p_block_reason = p_block_reason.clone(); Callback::from(move |e: InputEvent| { p_block_reason.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_block_until = { let p_block_until = p_block_until.clone(); Callback::from(move |e: InputEvent| { p_block_until.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_name = { let plan_name = plan_name.clone(); Callback::from(move |e: InputEvent| { plan_name.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_fee = { let plan_fee = plan_fee.clone(); Callback::from(move |e: InputEvent| { plan_fee.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_per_km = { let plan_per_km = plan_per_km.clone(); Callback::from(move |e: InputEvent| { plan_per_km.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_prio = { let plan_prio = plan_prio.clone(); Callback::from(move |e: InputEvent| { plan_prio.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_max = { let plan_max = plan_max.clone(); Callback::from(move |e: InputEvent| { plan_max.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_per_hour = { let plan_per_hour = plan_per_hour.clone(); Callback::from(move |e: InputEvent| { plan_per_hour.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_hour_rate = { let hour_rate = hour_rate.clone(); Callback::from(move |e: InputEvent| { hour_rate.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_plan_classes = { let plan_classes = plan_classes.clone(); Callback::from(move |e: InputEvent| { plan_classes.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_age = { let p_age = p_age.clone(); Callback::from(move |e: InputEvent| { p_age.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_min_age = { let p_min_age = p_min_age.clone(); Callback::from(move |e: InputEvent| { p_min_age.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_requirements = { let p_requirements = p_requirements.clone(); Callback::from(move |e: InputEvent| { p_requirements.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_p_reminders = { let p_reminders = p_reminders.clone(); Callback::from(move |e: InputEvent| { p_reminders.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_c_id = { let c_id = c_id.clone(); Callback::from(move |e: InputEvent| { c_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_c_km = { let c_km = c_km.clone(); Callback::from(move |e: InputEvent| { c_km.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_c_age = { let c_age = c_age.clone(); Callback::from(move |e: InputEvent| { c_age.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_r_person = { let r_person = r_person.clone(); Callback::from(move |e: InputEvent| { r_person.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_r_car = { let r_car = r_car.clone(); Callback::from(move |e: InputEvent| { r_car.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
-    let on_r_prio = { let r_prio = r_prio.clone(); Callback::from(move |e: InputEvent| { r_prio.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_class = { let r_class = r_class.clone(); Callback::from(move |e: InputEvent| { r_class.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_seats = { let r_seats = r_seats.clone(); Callback::from(move |e: InputEvent| { r_seats.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_transmission = { let r_transmission = r_transmission.clone(); Callback::from(move |e: InputEvent| { r_transmission.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_features = { let r_features = r_features.clone(); Callback::from(move |e: InputEvent| { r_features.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_station = { let r_station = r_station.clone(); Callback::from(move |e: InputEvent| { r_station.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_start = { let r_start = r_start.clone(); Callback::from(move |e: InputEvent| { r_start.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_end = { let r_end = r_end.clone(); Callback::from(move |e: InputEvent| { r_end.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_class_id = { let r_class_id = r_class_id.clone(); Callback::from(move |e: InputEvent| { r_class_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_strategy = { let r_strategy = r_strategy.clone(); Callback::from(move |e: InputEvent| { r_strategy.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_max_open = { let r_max_open = r_max_open.clone(); Callback::from(move |e: InputEvent| { r_max_open.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_max_per_day = { let r_max_per_day = r_max_per_day.clone(); Callback::from(move |e: InputEvent| { r_max_per_day.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_cooldown = { let r_cooldown = r_cooldown.clone(); Callback::from(move |e: InputEvent| { r_cooldown.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_pickup = { let r_pickup = r_pickup.clone(); Callback::from(move |e: InputEvent| { r_pickup.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_r_expiry = { let r_expiry = r_expiry.clone(); Callback::from(move |e: InputEvent| { r_expiry.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_ret_person = { let ret_person = ret_person.clone(); Callback::from(move |e: InputEvent| { ret_person.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_ret_car = { let ret_car = ret_car.clone(); Callback::from(move |e: InputEvent| { ret_car.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_ret_km = { let ret_km = ret_km.clone(); Callback::from(move |e: InputEvent| { ret_km.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_m_day = { let m_day = m_day.clone(); Callback::from(move |e: InputEvent| { m_day.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_m_duration = { let m_duration = m_duration.clone(); Callback::from(move |e: InputEvent| { m_duration.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_m_capacity = { let m_capacity = m_capacity.clone(); Callback::from(move |e: InputEvent| { m_capacity.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sr_start = { let sr_start = sr_start.clone(); Callback::from(move |e: InputEvent| { sr_start.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sr_km = { let sr_km = sr_km.clone(); Callback::from(move |e: InputEvent| { sr_km.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sr_cost = { let sr_cost = sr_cost.clone(); Callback::from(move |e: InputEvent| { sr_cost.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sr_notes = { let sr_notes = sr_notes.clone(); Callback::from(move |e: InputEvent| { sr_notes.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_dmg_severity = { let dmg_severity = dmg_severity.clone(); Callback::from(move |e: InputEvent| { dmg_severity.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_dmg_description = { let dmg_description = dmg_description.clone(); Callback::from(move |e: InputEvent| { dmg_description.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_dmg_location = { let dmg_location = dmg_location.clone(); Callback::from(move |e: InputEvent| { dmg_location.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_dmg_repair_days = { let dmg_repair_days = dmg_repair_days.clone(); Callback::from(move |e: InputEvent| { dmg_repair_days.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_dmg_id = { let dmg_id = dmg_id.clone(); Callback::from(move |e: InputEvent| { dmg_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_powertrain = { let c_powertrain = c_powertrain.clone(); Callback::from(move |e: InputEvent| { c_powertrain.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_class = { let c_class = c_class.clone(); Callback::from(move |e: InputEvent| { c_class.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_seats = { let c_seats = c_seats.clone(); Callback::from(move |e: InputEvent| { c_seats.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_transmission = { let c_transmission = c_transmission.clone(); Callback::from(move |e: InputEvent| { c_transmission.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_features = { let c_features = c_features.clone(); Callback::from(move |e: InputEvent| { c_features.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_ret_energy = { let ret_energy = ret_energy.clone(); Callback::from(move |e: InputEvent| { ret_energy.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_min_energy = { let min_energy = min_energy.clone(); Callback::from(move |e: InputEvent| { min_energy.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_c_station = { let c_station = c_station.clone(); Callback::from(move |e: InputEvent| { c_station.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_ret_station = { let ret_station = ret_station.clone(); Callback::from(move |e: InputEvent| { ret_station.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_st_id = { let st_id = st_id.clone(); Callback::from(move |e: InputEvent| { st_id.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_st_name = { let st_name = st_name.clone(); Callback::from(move |e: InputEvent| { st_name.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_st_lat = { let st_lat = st_lat.clone(); Callback::from(move |e: InputEvent| { st_lat.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_st_lon = { let st_lon = st_lon.clone(); Callback::from(move |e: InputEvent| { st_lon.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_st_capacity = { let st_capacity = st_capacity.clone(); Callback::from(move |e: InputEvent| { st_capacity.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sim_hours = { let sim_hours = sim_hours.clone(); Callback::from(move |e: InputEvent| { sim_hours.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sc_seed = { let sc_seed = sc_seed.clone(); Callback::from(move |e: InputEvent| { sc_seed.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sc_days = { let sc_days = sc_days.clone(); Callback::from(move |e: InputEvent| { sc_days.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sc_rate = { let sc_rate = sc_rate.clone(); Callback::from(move |e: InputEvent| { sc_rate.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
+    let on_sc_cars = { let sc_cars = sc_cars.clone(); Callback::from(move |e: InputEvent| { sc_cars.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
     let on_sim_days = { let sim_days = sim_days.clone(); Callback::from(move |e: InputEvent| { sim_days.set(e.target_unchecked_into::<web_sys::HtmlInputElement>().value()); }) };
 
     // ========== Render current tab ==========
@@ -373,14 +1711,103 @@ fn app() -> Html {
                 <div style={row_style}>
                     <input style={input_style} placeholder="Person-ID" value={(*p_id).clone()} oninput={on_p_id}/>
                     <input style={input_style} placeholder="license_valid_days" value={(*p_days).clone()} oninput={on_p_days}/>
+                    <input style={input_style} placeholder="Führerscheinklassen (z.B. B, BE; leer = B)" value={(*p_licenses).clone()} oninput={on_p_licenses}/>
+                    <input style={input_style} placeholder="Alter in Jahren (optional)" value={(*p_age).clone()} oninput={on_p_age}/>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Vorname" value={(*p_first).clone()} oninput={on_p_first}/>
+                    <input style={input_style} placeholder="Nachname" value={(*p_last).clone()} oninput={on_p_last}/>
+                    <input style={input_style} placeholder="E-Mail (optional)" value={(*p_email).clone()} oninput={on_p_email}/>
+                    <input style={input_style} placeholder="Telefon (optional)" value={(*p_phone).clone()} oninput={on_p_phone}/>
                 </div>
                 <div style={row_style}>
                     <button style={button_style} onclick={on_add_person}>{"Add Person"}</button>
                     <button style={button_style} onclick={on_remove_person}>{"Remove Person (by ID)"}</button>
                     <button style={button_style} onclick={on_renew_license}>{"Renew License (ID + days)"}</button>
                 </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Sperrgrund (manual/unpaid/damage)" value={(*p_block_reason).clone()} oninput={on_p_block_reason}/>
+                    <input style={input_style} placeholder="gesperrt bis Tag (leer = unbefristet)" value={(*p_block_until).clone()} oninput={on_p_block_until}/>
+                    <button style={button_style} onclick={on_suspend_person}>{"Suspend (ID + Grund)"}</button>
+                    <button style={button_style} onclick={on_unsuspend_person}>{"Unsuspend (ID + Grund)"}</button>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder={format!("Kulanzfrist Tage (aktuell {})", model.policy.license_grace_days)} value={(*p_grace).clone()} oninput={on_p_grace}/>
+                    <input style={input_style} placeholder={format!("Erinnerungen (aktuell {:?})", model.policy.license_reminder_days)} value={(*p_reminders).clone()} oninput={on_p_reminders}/>
+                    <input style={input_style} placeholder={format!("Mindestalter (aktuell {})", model.policy.min_age_years)} value={(*p_min_age).clone()} oninput={on_p_min_age}/>
+                    <input style={input_style} placeholder="Klassen, z.B. van=C1 (sonst B)" value={(*p_requirements).clone()} oninput={on_p_requirements}/>
+                    <button style={button_style} onclick={on_set_license_policy}>{"Set License Policy"}</button>
+                </div>
                 <p style={small}>{format!("Persons: {}", model.persons.len())}</p>
-                <ul>{ for model.persons.iter().map(|p| html!{ <li>{format!("{} | days:{} | status:{:?}", p.identifier, p.license_valid_days, p.status)}</li> }) }</ul>
+                <ul>{ for model.persons.iter().map(|p| html!{ <li>{format!(
+                    "{} | {} {} | {} | Klassen:{:?} | Alter:{} | Tarif:{} | No-Shows:{} | days:{} | status:{:?}{}",
+                    p.identifier,
+                    p.first_name,
+                    p.last_name,
+                    p.email.as_deref().unwrap_or("-"),
+                    p.license_classes,
+                    p.age_years(model.current_day).map_or("-".to_string(), |a| a.to_string()),
+                    p.subscription.as_ref().map_or("-".to_string(), |s| format!("{} (Verlängerung Tag {})", s.plan, s.renewal_day)),
+                    match p.reservation_cooldown_until {
+                        Some(until) if until > model.current_day => format!("{} (reservieren ab Tag {})", p.no_shows, until),
+                        _ => p.no_shows.to_string(),
+                    },
+                    p.license_valid_days,
+                    p.status,
+                    p.blocks
+                        .iter()
+                        .map(|b| match b.until_day {
+                            Some(until) => format!(" | {:?} bis Tag {}", b.reason, until),
+                            None => format!(" | {:?} seit Tag {}", b.reason, b.since_day),
+                        })
+                        .collect::<String>()
+                )}</li> }) }</ul>
+                <h3>{"Tarife"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Tarifname" value={(*plan_name).clone()} oninput={on_plan_name}/>
+                    <input style={input_style} placeholder="Monatsgebühr (Cent)" value={(*plan_fee).clone()} oninput={on_plan_fee}/>
+                    <input style={input_style} placeholder="Preis je km (Cent)" value={(*plan_per_km).clone()} oninput={on_plan_per_km}/>
+                    <input style={input_style} placeholder="Preis je Stunde (Cent, leer = Standard)" value={(*plan_per_hour).clone()} oninput={on_plan_per_hour}/>
+                    <input style={input_style} placeholder="Reservierungspriorität" value={(*plan_prio).clone()} oninput={on_plan_prio}/>
+                    <input style={input_style} placeholder="max. offene Reservierungen" value={(*plan_max).clone()} oninput={on_plan_max}/>
+                    <input style={input_style} placeholder="Fahrzeugklassen (leer = alle)" value={(*plan_classes).clone()} oninput={on_plan_classes}/>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_add_plan}>{"Add Plan"}</button>
+                    <button style={button_style} onclick={on_remove_plan}>{"Remove Plan (by name)"}</button>
+                    <button style={button_style} onclick={on_subscribe}>{"Subscribe (Person-ID + Tarif)"}</button>
+                    <button style={button_style} onclick={on_unsubscribe}>{"Unsubscribe (Person-ID)"}</button>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder={format!("Standard-Zeitpreis in Cent/Std. (aktuell {})", model.policy.per_hour_cents)} value={(*hour_rate).clone()} oninput={on_hour_rate}/>
+                    <button style={button_style} onclick={on_set_hour_rate}>{"Set Hourly Rate"}</button>
+                </div>
+                <ul>{ for model.plans.iter().map(|plan| html!{ <li>{format!(
+                    "{} | {:.2} €/Monat | {:.2} €/km | {} | prio {} | max. {} Reservierungen | Klassen:{} | Mitglieder:{}",
+                    plan.name,
+                    plan.monthly_fee_cents as f32 / 100.0,
+                    plan.per_km_cents as f32 / 100.0,
+                    plan.per_hour_cents.map_or("Standard-Zeitpreis".to_string(), |cents| format!("{:.2} €/Std.", cents as f32 / 100.0)),
+                    plan.priority,
+                    plan.max_reservations,
+                    if plan.allowed_classes.is_empty() { "alle".to_string() } else { format!("{:?}", plan.allowed_classes) },
+                    model.persons.iter().filter(|p| p.subscription.as_ref().is_some_and(|s| s.plan == plan.name)).count()
+                )}</li> }) }</ul>
+                <h3>{format!("Führerschein läuft bald ab (≤ {} Tage bis zur Sperre)", LICENSE_WARN_DAYS)}</h3>
+                <ul>{ for expiring_licenses(&model, LICENSE_WARN_DAYS).iter().map(|e| {
+                    let style = if e.grace_days_left.is_some() || e.days_until_block <= 7 {
+                        "background:#fde2e1; padding:2px 6px; border-radius:6px;"
+                    } else {
+                        "background:#fff4d6; padding:2px 6px; border-radius:6px;"
+                    };
+                    let text = match e.grace_days_left {
+                        Some(days) => format!("{} | abgelaufen, Kulanzfrist noch {} Tage", e.person_id, days),
+                        None => format!("{} | gültig noch {} Tage, Sperre in {} Tagen", e.person_id, e.license_valid_days, e.days_until_block),
+                    };
+                    html!{ <li><span style={style}>{text}</span></li> }
+                }) }</ul>
+                <h3>{"Nachrichten"}</h3>
+                <ul>{ for model.notifications.iter().rev().take(20).map(|n| html!{ <li>{format!("Tag {} | {} | {}", n.day, n.person_id, n.message)}</li> }) }</ul>
             </section>
         },
         Tab::Cars => html! {
@@ -390,14 +1817,64 @@ fn app() -> Html {
                     <input style={input_style} placeholder="Car-ID" value={(*c_id).clone()} oninput={on_c_id}/>
                     <input style={input_style} placeholder="mileage" value={(*c_km).clone()} oninput={on_c_km}/>
                     <input style={input_style} placeholder="age_days" value={(*c_age).clone()} oninput={on_c_age}/>
+                    <input style={input_style} placeholder="powertrain (combustion/electric)" value={(*c_powertrain).clone()} oninput={on_c_powertrain}/>
+                    <input style={input_style} placeholder="Station-ID (optional)" value={(*c_station).clone()} oninput={on_c_station}/>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Klasse (mini/compact/midsize/suv/van)" value={(*c_class).clone()} oninput={on_c_class}/>
+                    <input style={input_style} placeholder="Sitze" value={(*c_seats).clone()} oninput={on_c_seats}/>
+                    <input style={input_style} placeholder="Getriebe (manual/automatic)" value={(*c_transmission).clone()} oninput={on_c_transmission}/>
+                    <input style={input_style} placeholder="Ausstattung (kommagetrennt)" value={(*c_features).clone()} oninput={on_c_features}/>
                 </div>
                 <div style={row_style}>
                     <button style={button_style} onclick={on_add_car}>{"Add Car"}</button>
                     <button style={button_style} onclick={on_remove_car}>{"Remove Car (by ID)"}</button>
                 </div>
+                <h3>{"Werkstatt"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Tag (für Planung)" value={(*m_day).clone()} oninput={on_m_day}/>
+                    <input style={input_style} placeholder="Dauer / Zusatztage" value={(*m_duration).clone()} oninput={on_m_duration}/>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_schedule_maintenance}>{"Schedule Maintenance"}</button>
+                    <button style={button_style} onclick={on_schedule_tuv}>{"Schedule TÜV"}</button>
+                    <button style={button_style} onclick={on_start_maintenance}>{"Start Maintenance"}</button>
+                    <button style={button_style} onclick={on_start_tuv}>{"Start TÜV"}</button>
+                    <button style={button_style} onclick={on_extend_maintenance}>{"Extend"}</button>
+                    <button style={button_style} onclick={on_end_maintenance}>{"End early"}</button>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Werkstattkapazität (leer = unbegrenzt)" value={(*m_capacity).clone()} oninput={on_m_capacity}/>
+                    <button style={button_style} onclick={on_set_capacity}>{"Set Capacity"}</button>
+                    <input style={input_style} placeholder={format!("Mindestfüllstand % (aktuell {})", model.policy.min_energy_level)} value={(*min_energy).clone()} oninput={on_min_energy}/>
+                    <button style={button_style} onclick={on_set_min_energy}>{"Set Min. Energy"}</button>
+                </div>
+                <p style={small}>{format!("Geplante Termine: {}", model.scheduled_services.len())}</p>
+                <ul>{ for model.scheduled_services.iter().map(|s| html!{ <li>{format!("Tag {} | {} | {:?} | {} Tage", s.day, s.car_id, s.kind, s.duration_days)}</li> }) }</ul>
+                <h3>{"Offene Schäden"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Schadens-ID" value={(*dmg_id).clone()} oninput={on_dmg_id}/>
+                    <button style={button_style} onclick={on_resolve_damage}>{"Resolve Damage"}</button>
+                </div>
+                <ul>{ for model.damages.iter().filter(|d| d.resolved_day.is_none()).map(|d| html!{ <li>{format!("#{} | {} | {:?} | {} ({}) | gemeldet Tag {} von {}", d.id, d.car_id, d.severity, d.description, d.location, d.reported_day, d.person_id)}</li> }) }</ul>
+                <h3>{"Service-Historie"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Start-Tag (Ende = Tag oben)" value={(*sr_start).clone()} oninput={on_sr_start}/>
+                    <input style={input_style} placeholder="km beim Service" value={(*sr_km).clone()} oninput={on_sr_km}/>
+                    <input style={input_style} placeholder="Kosten (Cent)" value={(*sr_cost).clone()} oninput={on_sr_cost}/>
+                    <input style={input_style} placeholder="Notizen" value={(*sr_notes).clone()} oninput={on_sr_notes}/>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_record_maintenance}>{"Record Maintenance"}</button>
+                    <button style={button_style} onclick={on_record_tuv}>{"Record TÜV"}</button>
+                </div>
+                <p style={small}>{format!("Historie für '{}':", (*c_id).trim())}</p>
+                <ul>{ for model.get_service_history((*c_id).trim()).iter().map(|r| html!{ <li>{format!("{:?} | Tag {}-{} | km:{} | {:.2} € | {}", r.kind, r.start_day, r.end_day, r.mileage, r.cost_cents as f32 / 100.0, r.notes)}</li> }) }</ul>
                 <p style={small}>{format!("Cars: {}", model.cars.len())}</p>
                 <p style={small}>{format!("Available: {:?}", model.get_available_cars())}</p>
-                <ul>{ for model.cars.iter().map(|c| html!{ <li>{format!("{} | km:{} | age:{} | rentals:{} | status:{:?}", c.identifier, c.mileage, c.age_days, c.rental_count, c.status)}</li> }) }</ul>
+                <ul>{ for model.cars.iter().map(|c| html!{ <li>{format!("{} | km:{} | age:{} | rentals:{} | {:?}, {} Sitze, {:?} | {:?} {}% | {} | station:{} | status:{:?}", c.identifier, c.mileage, c.age_days, c.rental_count, c.class, c.seats, c.transmission, c.powertrain, c.energy_level, c.features.join(", "), c.station_id.as_deref().unwrap_or("-"), c.status)}</li> }) }</ul>
+                <h3>{format!("Bald fällig (≤ {} Tage)", DUE_SOON_DAYS)}</h3>
+                <ul>{ for due_soon(&model, DUE_SOON_DAYS).iter().map(|f| html!{ <li>{format!("{} | Wartung in {} km (~{}) | TÜV in {} km (~{}) | Ausmusterung in ~{} Tagen", f.car_id, f.km_until_maintenance, fmt_days(f.days_until_maintenance), f.km_until_tuv, fmt_days(f.days_until_tuv), f.days_until_retirement)}</li> }) }</ul>
             </section>
         },
         Tab::Reservations => html! {
@@ -406,7 +1883,8 @@ fn app() -> Html {
                 <div style={row_style}>
                     <input style={input_style} placeholder="Person-ID" value={(*r_person).clone()} oninput={on_r_person}/>
                     <input style={input_style} placeholder="Car-ID" value={(*r_car).clone()} oninput={on_r_car}/>
-                    <input style={input_style} placeholder="priority" value={(*r_prio).clone()} oninput={on_r_prio}/>
+                    <input style={input_style} placeholder="Beginn 'Tag HH:MM' (optional)" value={(*r_start).clone()} oninput={on_r_start}/>
+                    <input style={input_style} placeholder="Ende 'Tag HH:MM' (optional)" value={(*r_end).clone()} oninput={on_r_end}/>
                 </div>
                 <div style={row_style}>
                     <button style={button_style} onclick={on_reserve}>{"Reserve"}</button>
@@ -414,7 +1892,52 @@ fn app() -> Html {
                     <button style={button_style} onclick={on_process_reservations}>{"Process Reservations"}</button>
                 </div>
                 <p style={small}>{format!("Reservations: {}", model.reservations.len())}</p>
-                <ul>{ for model.reservations.iter().map(|r| html!{ <li>{format!("{} -> {} (prio {})", r.person_id, r.car_id, r.priority)}</li> }) }</ul>
+                <ul>{ for model.reservations.iter().map(|r| html!{ <li>{format!("{} -> {} (prio {}){}", r.person_id, r.car_id, r.priority, fmt_window(r.start_at, r.end_at))}</li> }) }</ul>
+                <h3>{"Nach Merkmalen"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Klasse (optional)" value={(*r_class).clone()} oninput={on_r_class}/>
+                    <input style={input_style} placeholder="min. Sitze (optional)" value={(*r_seats).clone()} oninput={on_r_seats}/>
+                    <input style={input_style} placeholder="Getriebe (optional)" value={(*r_transmission).clone()} oninput={on_r_transmission}/>
+                    <input style={input_style} placeholder="Ausstattung (kommagetrennt)" value={(*r_features).clone()} oninput={on_r_features}/>
+                    <input style={input_style} placeholder="Station-ID (optional)" value={(*r_station).clone()} oninput={on_r_station}/>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_reserve_class}>{"Reserve (Person-ID von oben)"}</button>
+                    <input style={input_style} placeholder="Reservierungs-Nr." value={(*r_class_id).clone()} oninput={on_r_class_id}/>
+                    <button style={button_style} onclick={on_cancel_class_reservation}>{"Cancel"}</button>
+                </div>
+                <ul>{ for model.class_reservations.iter().map(|r| html!{ <li>{format!("#{} {} -> {:?} (prio {}){}", r.id, r.person_id, r.filter, r.priority, fmt_window(r.start_at, r.end_at))}</li> }) }</ul>
+                <h3>{"Zuteilung"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder={format!("priority/fifo/aging/matching (aktuell {:?})", model.policy.allocation)} value={(*r_strategy).clone()} oninput={on_r_strategy}/>
+                    <button style={button_style} onclick={on_set_strategy}>{"Set Strategy"}</button>
+                    <input style={input_style} placeholder="Verfall in Tagen (leer = nie)" value={(*r_expiry).clone()} oninput={on_r_expiry}/>
+                    <button style={button_style} onclick={on_set_expiry}>{"Set Expiry"}</button>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder={format!("max. offen je Person (aktuell {:?})", model.policy.max_open_reservations)} value={(*r_max_open).clone()} oninput={on_r_max_open}/>
+                    <input style={input_style} placeholder={format!("max. neue je Tag (aktuell {:?})", model.policy.max_reservations_per_day)} value={(*r_max_per_day).clone()} oninput={on_r_max_per_day}/>
+                    <input style={input_style} placeholder={format!("Sperrfrist nach No-Show (aktuell {} Tage)", model.policy.no_show_cooldown_days)} value={(*r_cooldown).clone()} oninput={on_r_cooldown}/>
+                    <input style={input_style} placeholder={format!("Abholfrist in Tagen (aktuell {:?})", model.policy.pickup_deadline_days)} value={(*r_pickup).clone()} oninput={on_r_pickup}/>
+                    <button style={button_style} onclick={on_set_quotas}>{"Set Quotas"}</button>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_toggle_retired_rule}>{format!("Auto ausgemustert: {:?}", model.policy.on_car_retired)}</button>
+                    <button style={button_style} onclick={on_toggle_blocked_rule}>{format!("Person gesperrt: {:?}", model.policy.on_person_blocked)}</button>
+                </div>
+                <ul>{ for compare_strategies(&model).iter().map(|o| html!{ <li>{format!("{:?}: {} erfüllt {:?}", o.strategy, o.fulfilled, o.assignments)}</li> }) }</ul>
+                <h3>{"Ereignisse"}</h3>
+                <ul>{ for model.reservation_events.iter().rev().take(20).map(|e| html!{ <li>{format!("Tag {} | {} | {:?} | {}", e.day, e.person_id, e.kind, e.reason)}</li> }) }</ul>
+                <h3>{"Verlauf"}</h3>
+                <ul>{ for model.reservation_history.iter().rev().take(50).map(|r| {
+                    let target = match (&r.car_id, r.class_reservation_id) {
+                        (Some(car_id), _) => car_id.clone(),
+                        (None, Some(id)) => format!("Merkmale #{}", id),
+                        (None, None) => "-".to_string(),
+                    };
+                    let reason = r.transitions.last().map(|t| t.reason.clone()).unwrap_or_default();
+                    html!{ <li>{format!("Tag {} | {} -> {} | {:?} | {}", r.created_day, r.person_id, target, r.state, reason)}</li> }
+                }) }</ul>
             </section>
         },
         Tab::Rentals => html! {
@@ -425,10 +1948,63 @@ fn app() -> Html {
                     <input style={input_style} placeholder="Person-ID" value={(*ret_person).clone()} oninput={on_ret_person}/>
                     <input style={input_style} placeholder="Car-ID" value={(*ret_car).clone()} oninput={on_ret_car}/>
                     <input style={input_style} placeholder="driven_km" value={(*ret_km).clone()} oninput={on_ret_km}/>
+                    <input style={input_style} placeholder="Tank/Akku in % (optional)" value={(*ret_energy).clone()} oninput={on_ret_energy}/>
+                    <input style={input_style} placeholder="Rückgabestation (optional)" value={(*ret_station).clone()} oninput={on_ret_station}/>
+                </div>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Schaden: minor/major/breakdown (optional)" value={(*dmg_severity).clone()} oninput={on_dmg_severity}/>
+                    <input style={input_style} placeholder="Beschreibung" value={(*dmg_description).clone()} oninput={on_dmg_description}/>
+                    <input style={input_style} placeholder="Stelle am Auto" value={(*dmg_location).clone()} oninput={on_dmg_location}/>
+                    <input style={input_style} placeholder="Reparaturtage (optional)" value={(*dmg_repair_days).clone()} oninput={on_dmg_repair_days}/>
                     <button style={button_style} onclick={on_return}>{"Return Car"}</button>
+                    <button style={button_style} onclick={on_pick_up}>{"Pick Up"}</button>
                 </div>
                 <p style={small}>{format!("Rentals: {}", model.rentals.len())}</p>
-                <ul>{ for model.rentals.iter().map(|(p,c)| html!{ <li>{format!("{} -> {}", p, c)}</li> }) }</ul>
+                <ul>{ for model.rentals.iter().map(|(p,c)| {
+                    let since = model.cars.iter().find(|car| &car.identifier == c).and_then(|car| car.rented_at).map(format_time);
+                    let pickup = model.pickup_due_day(p, c).map(|day| format!(", abholen bis Tag {}", day)).unwrap_or_default();
+                    html!{ <li>{format!("{} -> {} (seit {}{})", p, c, since.unwrap_or_else(|| "-".to_string()), pickup)}</li> }
+                }) }</ul>
+                <h3>{"Gebühren"}</h3>
+                <ul>{ for model.charges.iter().map(|c| html!{ <li>{format!("Tag {} | {} | {} | {:?} | {:.2} €", c.day, c.person_id, c.car_id, c.kind, c.amount_cents as f32 / 100.0)}</li> }) }</ul>
+            </section>
+        },
+        Tab::Stations => html! {
+            <section style={panel_style}>
+                <h2>{"Stations"}</h2>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Station-ID" value={(*st_id).clone()} oninput={on_st_id}/>
+                    <input style={input_style} placeholder="Name" value={(*st_name).clone()} oninput={on_st_name}/>
+                    <input style={input_style} placeholder="Breitengrad" value={(*st_lat).clone()} oninput={on_st_lat}/>
+                    <input style={input_style} placeholder="Längengrad" value={(*st_lon).clone()} oninput={on_st_lon}/>
+                    <input style={input_style} placeholder="Kapazität" value={(*st_capacity).clone()} oninput={on_st_capacity}/>
+                </div>
+                <div style={row_style}>
+                    <button style={button_style} onclick={on_add_station}>{"Add Station"}</button>
+                    <button style={button_style} onclick={on_remove_station}>{"Remove Station (by ID)"}</button>
+                    <button style={button_style} onclick={on_move_car}>{"Move Car (Car-ID aus Tab Cars) hierher"}</button>
+                </div>
+                <p style={small}>{format!("Stations: {}", model.stations.len())}</p>
+                <ul>{ for model.stations.iter().map(|s| {
+                    let occupancy = model.get_station_occupancy(&s.identifier).unwrap_or(0);
+                    html!{
+                        <li>
+                            {format!("{} ({}) | {:.4}, {:.4} | {}/{} belegt | verfügbar: {:?} ", s.identifier, s.name, s.latitude, s.longitude, occupancy, s.capacity, model.get_available_cars_at(&s.identifier))}
+                            { rate_bar("Belegung", if s.capacity == 0 { 0.0 } else { occupancy as f32 / s.capacity as f32 }) }
+                        </li>
+                    }
+                }) }</ul>
+                <h3>{"Umsetzungsplan"}</h3>
+                {{
+                    let plan = plan_rebalancing(&model);
+                    html! {
+                        <>
+                            <p style={small}>{format!("Offene Reservierungen ohne Auto an der Station: {} -> {} nach dem Plan", plan.unmet_before, plan.unmet_after)}</p>
+                            <ul>{ for plan.moves.iter().map(|m| html!{ <li>{format!("{}: {} -> {} ({:.1} km)", m.car_id, m.from_station, m.to_station, m.distance_km)}</li> }) }</ul>
+                            <button style={button_style} onclick={on_apply_rebalancing}>{"Plan anwenden"}</button>
+                        </>
+                    }
+                }}
             </section>
         },
         Tab::Simulation => html! {
@@ -436,13 +2012,80 @@ fn app() -> Html {
                 <h2>{"Simulation"}</h2>
                 <div style={row_style}>
                     <input style={input_style} placeholder="n days" value={(*sim_days).clone()} oninput={on_sim_days}/>
-                    <button style={button_style} onclick={on_simulate}>{"Simulate n days"}</button>
+                    <button style={button_style} onclick={on_simulate} disabled={real_time}>{"Simulate n days"}</button>
+                    <input style={input_style} placeholder="n hours" value={(*sim_hours).clone()} oninput={on_sim_hours}/>
+                    <button style={button_style} onclick={on_advance_hours} disabled={real_time}>{"Advance n hours"}</button>
                     <button style={button_style} onclick={on_reset}>{"Reset state"}</button>
                 </div>
-                <p style={small}>{format!("Current day: {}", model.current_day)}</p>
+                <p style={small}>{format!("Current day: {} ({})", model.current_day, format_time(model.now()))}</p>
                 <p style={small}>{"Hinweis: simulate_n_days() verarbeitet am Ende jedes Tages process_reservations()."}</p>
+                if real_time {
+                    <p style={small}>{"Echtzeitbetrieb: Das Backend schaltet den Tag um Mitternacht weiter und führt dabei dieselbe Tagesverarbeitung aus."}</p>
+                }
+                <h3>{"Entwicklung der Flotte"}</h3>
+                { fleet_chart(&model.history) }
+                <h3>{"Szenario (Was-wäre-wenn)"}</h3>
+                <div style={row_style}>
+                    <input style={input_style} placeholder="Seed" value={(*sc_seed).clone()} oninput={on_sc_seed}/>
+                    <input style={input_style} placeholder="Tage (max. 365)" value={(*sc_days).clone()} oninput={on_sc_days}/>
+                    <input style={input_style} placeholder="Wünsche je Tag" value={(*sc_rate).clone()} oninput={on_sc_rate}/>
+                    <input style={input_style} placeholder="zusätzliche Autos" value={(*sc_cars).clone()} oninput={on_sc_cars}/>
+                    <button style={button_style} onclick={on_run_scenario}>{"Run Scenario"}</button>
+                </div>
+                if let Some(report) = &*scenario {
+                    <p style={small}>{format!(
+                        "Flotte: {} | Wünsche: {} | erfüllt: {} | nicht erfüllt: {} | offen: {} | Auslastung: {:.0} % | ausgemustert: {} | Umsatz: {:.2} €",
+                        report.fleet_size,
+                        report.requests,
+                        report.fulfilled,
+                        report.unmet_demand,
+                        report.still_open,
+                        report.utilization * 100.0,
+                        report.cars_retired,
+                        report.revenue_cents as f64 / 100.0
+                    )}</p>
+                    <ul>{ for report.daily.iter().map(|d| html!{ <li>{format!("Tag {} | {} Wünsche | {} erfüllt | {} nicht erfüllt | Auslastung {:.0} %", d.day, d.requests, d.fulfilled, d.unmet, d.utilization * 100.0)}</li> }) }</ul>
+                }
             </section>
         },
+        Tab::Dashboard => {
+            let stats = fleet_statistics(&model);
+            let status_bars = vec![
+                ("Available".to_string(), stats.cars_per_status.available),
+                ("Rented".to_string(), stats.cars_per_status.rented),
+                ("Maintenance".to_string(), stats.cars_per_status.maintenance),
+                ("TÜV".to_string(), stats.cars_per_status.tuv),
+                ("Repair".to_string(), stats.cars_per_status.repair),
+                ("Charging".to_string(), stats.cars_per_status.charging),
+                ("Relocating".to_string(), stats.cars_per_status.relocating),
+                ("Retired".to_string(), stats.cars_per_status.retired),
+            ];
+            let score_bars: Vec<(String, u32)> = stats.retirement_score_distribution.iter().map(|b| {
+                let label = match b.to {
+                    Some(to) => format!("{:.1} - {:.1}", b.from, to),
+                    None => format!("> {:.1}", b.from),
+                };
+                (label, b.count)
+            }).collect();
+            html! {
+                <section style={panel_style}>
+                    <h2>{"Dashboard"}</h2>
+                    <p style={small}>{format!("Tag {} | Autos: {} | Ø km: {:.0} | Ø Alter: {:.0} Tage", stats.current_day, stats.total_cars, stats.average_mileage, stats.average_age_days)}</p>
+                    { rate_bar("Auslastung", stats.utilization_rate) }
+                    { rate_bar("Erfüllungsquote Reservierungen", stats.fulfillment_rate) }
+                    <h3>{"Autos nach Status"}</h3>
+                    { bar_chart(&status_bars) }
+                    <h3>{"Verteilung retirement_score"}</h3>
+                    { bar_chart(&score_bars) }
+                    <h3>{"Bald fällig"}</h3>
+                    <p style={small}>{format!("Wartung: {:?}", stats.cars_near_maintenance)}</p>
+                    <p style={small}>{format!("TÜV: {:?}", stats.cars_near_tuv)}</p>
+                    <h3>{"Personen & Reservierungen"}</h3>
+                    <p style={small}>{format!("Gesperrte Personen: {:?}", stats.blocked_persons)}</p>
+                    <p style={small}>{format!("Offene Reservierungen: {}", stats.reservation_backlog)}</p>
+                </section>
+            }
+        }
     };
 
     html! {
@@ -453,11 +2096,24 @@ fn app() -> Html {
                 { tab_button(&tab, Tab::Cars, "Cars", set_tab_cars) }
                 { tab_button(&tab, Tab::Reservations, "Reservations", set_tab_res) }
                 { tab_button(&tab, Tab::Rentals, "Active Rentals", set_tab_rentals) }
+                { tab_button(&tab, Tab::Stations, "Stations", set_tab_stations) }
                 { tab_button(&tab, Tab::Simulation, "Simulation", set_tab_sim) }
+                { tab_button(&tab, Tab::Dashboard, "Dashboard", set_tab_dashboard) }
             </div>
             <p style="margin-top:12px; padding:10px 12px; border:1px solid #eee; border-radius:12px; background:#fafafa;">
                 <strong>{"Status: "}</strong>{(*info).clone()}
             </p>
+            <p style="color:#555; font-size:14px;">{ match &*clock {
+                Some(c) if c.mode == "real_time" => format!(
+                    "Uhr: Echtzeit ({}) | {} = {} | nächster Tag: {}",
+                    c.timezone,
+                    format_time(cs.now()),
+                    c.local_date,
+                    c.next_day_at.as_deref().unwrap_or("-")
+                ),
+                Some(_) => format!("Uhr: simuliert | {}", format_time(cs.now())),
+                None => format_time(cs.now()),
+            } }</p>
             {content}
         </main>
     }